@@ -0,0 +1,96 @@
+// Diff引擎性能基准
+//
+// 运行: cargo bench -p text-diff-desktop --bench diff_engine
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+#[allow(dead_code, unused_imports)]
+#[path = "../src/diff_engine/mod.rs"]
+mod diff_engine;
+
+use diff_engine::{DiffEngine, DiffOptions};
+
+fn char_options() -> DiffOptions {
+    DiffOptions {
+        ignore_case: false,
+        ignore_whitespace: false,
+        ignore_punctuation: false,
+        split_by_paragraph: false,
+        split_by_sentence: false,
+        use_web_worker: false,
+        ..DiffOptions::default()
+    }
+}
+
+/// 生成约 `size` 字节的合同样式文本（中英混排）
+fn generate_document(size: usize) -> String {
+    let mut text = String::with_capacity(size + 128);
+    let mut clause = 1;
+    while text.len() < size {
+        text.push_str(&format!(
+            "第{}条 甲方应于每月五日前向乙方支付服务费用。Clause {} shall survive termination.\n",
+            clause, clause
+        ));
+        clause += 1;
+    }
+    text
+}
+
+/// 在文档中均匀地做 `edits` 处小改动
+fn apply_edits(text: &str, edits: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let step = (lines.len() / edits.max(1)).max(1);
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            if i % step == 0 {
+                line.replace("服务费用", "技术服务费用")
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn bench_character_diff(c: &mut Criterion) {
+    let mut group = c.benchmark_group("character_diff");
+    group.sample_size(10);
+
+    for size in [10 * 1024, 100 * 1024, 1024 * 1024] {
+        let left = generate_document(size);
+        let right = apply_edits(&left, 20);
+        let engine = DiffEngine::new(char_options());
+
+        group.throughput(Throughput::Bytes(left.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, _| {
+            b.iter(|| engine.compute_diff(black_box(&left), black_box(&right)))
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_sentence_diff(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sentence_diff");
+    group.sample_size(10);
+
+    for size in [100 * 1024, 1024 * 1024] {
+        let left = generate_document(size);
+        let right = apply_edits(&left, 20);
+        let engine = DiffEngine::new(DiffOptions {
+            split_by_sentence: true,
+            ..char_options()
+        });
+
+        group.throughput(Throughput::Bytes(left.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, _| {
+            b.iter(|| engine.compute_diff(black_box(&left), black_box(&right)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_character_diff, bench_sentence_diff);
+criterion_main!(benches);
@@ -0,0 +1,97 @@
+// 可插拔的序列对比算法
+use std::collections::HashMap;
+
+use super::control::Deadline;
+use super::ops::DiffOp;
+use super::{histogram, lcs, myers, patience, DiffAlgorithm};
+
+/// 序列对比算法接口
+///
+/// 输入为已经驻留（intern）成整数编号的比较单元，两侧相同的单元编号相同。
+pub trait SequenceDiff: Send + Sync {
+    /// 算法名称，用于结果元数据和日志
+    fn name(&self) -> &'static str;
+
+    /// 计算把 `old` 变为 `new` 的编辑操作
+    fn diff(&self, old: &[u32], new: &[u32]) -> Vec<DiffOp> {
+        self.diff_before(old, new, &Deadline::never())
+    }
+
+    /// 在期限前计算编辑操作；超过期限后尚未求解的部分整体视为删除加插入
+    fn diff_before(&self, old: &[u32], new: &[u32], deadline: &Deadline) -> Vec<DiffOp>;
+}
+
+pub struct MyersDiff;
+
+impl SequenceDiff for MyersDiff {
+    fn name(&self) -> &'static str {
+        "Myers"
+    }
+
+    fn diff_before(&self, old: &[u32], new: &[u32], deadline: &Deadline) -> Vec<DiffOp> {
+        myers::diff_before(old, new, deadline)
+    }
+}
+
+pub struct PatienceDiff;
+
+impl SequenceDiff for PatienceDiff {
+    fn name(&self) -> &'static str {
+        "Patience"
+    }
+
+    fn diff_before(&self, old: &[u32], new: &[u32], deadline: &Deadline) -> Vec<DiffOp> {
+        patience::diff_before(old, new, deadline)
+    }
+}
+
+pub struct HistogramDiff;
+
+impl SequenceDiff for HistogramDiff {
+    fn name(&self) -> &'static str {
+        "Histogram"
+    }
+
+    fn diff_before(&self, old: &[u32], new: &[u32], deadline: &Deadline) -> Vec<DiffOp> {
+        histogram::diff_before(old, new, deadline)
+    }
+}
+
+pub struct LcsDiff;
+
+impl SequenceDiff for LcsDiff {
+    fn name(&self) -> &'static str {
+        "LCS"
+    }
+
+    fn diff_before(&self, old: &[u32], new: &[u32], deadline: &Deadline) -> Vec<DiffOp> {
+        lcs::diff_before(old, new, deadline)
+    }
+}
+
+impl DiffAlgorithm {
+    /// 获取算法实现
+    pub fn implementation(self) -> &'static dyn SequenceDiff {
+        match self {
+            DiffAlgorithm::Myers => &MyersDiff,
+            DiffAlgorithm::Patience => &PatienceDiff,
+            DiffAlgorithm::Histogram => &HistogramDiff,
+            DiffAlgorithm::LCS => &LcsDiff,
+        }
+    }
+}
+
+/// 把两侧的比较单元驻留为整数编号
+pub fn intern<'a>(left: &[&'a str], right: &[&'a str]) -> (Vec<u32>, Vec<u32>) {
+    let mut table: HashMap<&'a str, u32> = HashMap::new();
+
+    let mut id_of = |unit: &'a str| -> u32 {
+        let next_id = table.len() as u32;
+        *table.entry(unit).or_insert(next_id)
+    };
+
+    let left_ids = left.iter().map(|unit| id_of(unit)).collect();
+    let right_ids = right.iter().map(|unit| id_of(unit)).collect();
+
+    (left_ids, right_ids)
+}
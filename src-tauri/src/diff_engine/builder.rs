@@ -0,0 +1,192 @@
+// 差异项构建：把序列级编辑操作还原为带位置信息的 DiffItem
+use std::ops::Range;
+
+use super::ops::DiffOp;
+use super::position::TextCursor;
+use super::{DiffItem, DiffType, Position};
+
+/// 比较单元在原文中的字节区间，要求按顺序排列且首尾相接
+pub type Segments = [Range<usize>];
+
+/// 同一变更区域内删除与新增的配对方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pairing {
+    /// 不配对，分别输出删除和新增（字符级）
+    None,
+    /// 按单元一一配对为修改，多余部分保留为删除/新增（句子、段落级）
+    PerUnit,
+    /// 整个变更区域合并为一个修改（词级）
+    Region,
+}
+
+/// 将编辑操作转换为差异项，相邻的同类单元合并为一个区间
+pub fn build_items(
+    left: &str,
+    left_segments: &Segments,
+    right: &str,
+    right_segments: &Segments,
+    ops: &[DiffOp],
+    pairing: Pairing,
+) -> Vec<DiffItem> {
+    let mut builder = ItemBuilder::new(left, left_segments, right, right_segments);
+    let mut removed: Option<Range<usize>> = None;
+    let mut added: Option<Range<usize>> = None;
+
+    for op in ops {
+        match *op {
+            DiffOp::Equal { old_index, new_index, len } => {
+                builder.push_changes(removed.take(), added.take(), pairing);
+                builder.push(
+                    DiffType::Equal,
+                    old_index..old_index + len,
+                    new_index..new_index + len,
+                );
+            }
+            DiffOp::Delete { old_index, old_len, .. } => {
+                let range = removed.get_or_insert(old_index..old_index);
+                range.end = old_index + old_len;
+            }
+            DiffOp::Insert { new_index, new_len, .. } => {
+                let range = added.get_or_insert(new_index..new_index);
+                range.end = new_index + new_len;
+            }
+        }
+    }
+    builder.push_changes(removed, added, pairing);
+
+    builder.items
+}
+
+struct ItemBuilder<'a> {
+    left: &'a str,
+    left_segments: &'a Segments,
+    right: &'a str,
+    right_segments: &'a Segments,
+    left_cursor: TextCursor<'a>,
+    right_cursor: TextCursor<'a>,
+    // 变更区域中一侧为空时，用另一侧已处理到的单元下标定位
+    left_next: usize,
+    right_next: usize,
+    items: Vec<DiffItem>,
+}
+
+impl<'a> ItemBuilder<'a> {
+    fn new(
+        left: &'a str,
+        left_segments: &'a Segments,
+        right: &'a str,
+        right_segments: &'a Segments,
+    ) -> Self {
+        Self {
+            left,
+            left_segments,
+            right,
+            right_segments,
+            left_cursor: TextCursor::new(left),
+            right_cursor: TextCursor::new(right),
+            left_next: 0,
+            right_next: 0,
+            items: Vec::new(),
+        }
+    }
+
+    /// 输出一段连续变更
+    fn push_changes(
+        &mut self,
+        removed: Option<Range<usize>>,
+        added: Option<Range<usize>>,
+        pairing: Pairing,
+    ) {
+        let removed = removed.unwrap_or(self.left_next..self.left_next);
+        let added = added.unwrap_or(self.right_next..self.right_next);
+
+        if pairing == Pairing::Region && !removed.is_empty() && !added.is_empty() {
+            self.push(DiffType::Modify, removed, added);
+            return;
+        }
+
+        let paired = if pairing == Pairing::PerUnit {
+            removed.len().min(added.len())
+        } else {
+            0
+        };
+
+        for k in 0..paired {
+            self.push(
+                DiffType::Modify,
+                removed.start + k..removed.start + k + 1,
+                added.start + k..added.start + k + 1,
+            );
+        }
+
+        let rest_added = added.start + paired;
+        self.push(DiffType::Remove, removed.start + paired..removed.end, rest_added..rest_added);
+        self.push(DiffType::Add, removed.end..removed.end, rest_added..added.end);
+    }
+
+    /// 输出一个差异项，参数为两侧的单元下标区间
+    fn push(&mut self, diff_type: DiffType, left_units: Range<usize>, right_units: Range<usize>) {
+        if left_units.is_empty() && right_units.is_empty() {
+            return;
+        }
+
+        let left_bytes = byte_range(self.left, self.left_segments, &left_units);
+        let right_bytes = byte_range(self.right, self.right_segments, &right_units);
+        self.left_next = left_units.end;
+        self.right_next = right_units.end;
+
+        let left_text = &self.left[left_bytes.clone()];
+        let right_text = &self.right[right_bytes.clone()];
+
+        let position = Position {
+            left: self.left_cursor.range(left_bytes.start, left_bytes.end),
+            right: self.right_cursor.range(right_bytes.start, right_bytes.end),
+        };
+
+        let (content, original_content, line_number) = match diff_type {
+            DiffType::Add => (right_text.to_string(), None, position.right.start_line),
+            DiffType::Remove => (
+                left_text.to_string(),
+                Some(left_text.to_string()),
+                position.left.start_line,
+            ),
+            DiffType::Modify | DiffType::Move => (
+                right_text.to_string(),
+                Some(left_text.to_string()),
+                position.left.start_line,
+            ),
+            // 忽略大小写、空白等选项下两侧原文可能不同，以右侧为内容并保留左侧原文
+            DiffType::Equal => (
+                right_text.to_string(),
+                (left_text != right_text).then(|| left_text.to_string()),
+                position.left.start_line,
+            ),
+        };
+
+        self.items.push(DiffItem {
+            id: format!("diff-{}", self.items.len()),
+            diff_type,
+            content,
+            original_content,
+            line_number: Some(line_number),
+            position,
+            inline_changes: Vec::new(),
+            left_line: None,
+            right_line: None,
+            path: None,
+        });
+    }
+}
+
+/// 单元下标区间 → 原文字节区间；空区间定位到下一个单元的起点
+fn byte_range(text: &str, segments: &Segments, units: &Range<usize>) -> Range<usize> {
+    if units.is_empty() {
+        let at = segments
+            .get(units.start)
+            .map(|segment| segment.start)
+            .unwrap_or(text.len());
+        at..at
+    } else {
+        segments[units.start].start..segments[units.end - 1].end
+    }
+}
@@ -0,0 +1,306 @@
+// 源代码词法切分：按语言识别关键字、标识符、字面量、注释与运算符，供代码对比与语法高亮使用
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+/// 支持的编程语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CodeLanguage {
+    Rust,
+    Python,
+    JavaScript,
+    TypeScript,
+    Java,
+    /// C 与 C++
+    C,
+    Sql,
+}
+
+/// 词元类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenKind {
+    Keyword,
+    Identifier,
+    String,
+    Number,
+    Comment,
+    Operator,
+    Whitespace,
+}
+
+/// 词元：原文字节区间与类别
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeToken {
+    pub range: Range<usize>,
+    pub kind: TokenKind,
+}
+
+/// 多字符运算符，长的在前
+const OPERATORS: &[&str] = &[
+    "===", "!==", "**=", "<<=", ">>=", "...", "==", "!=", "<=", ">=", "&&", "||", "->", "=>", "::", "+=", "-=",
+    "*=", "/=", "%=", "&=", "|=", "^=", "++", "--", "<<", ">>", "**", "??", "?.",
+];
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn",
+    "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self",
+    "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+];
+
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+    "elif", "else", "except", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "nonlocal",
+    "not", "or", "pass", "raise", "return", "try", "while", "with", "yield",
+];
+
+const JAVASCRIPT_KEYWORDS: &[&str] = &[
+    "async", "await", "break", "case", "catch", "class", "const", "continue", "debugger", "default", "delete", "do",
+    "else", "export", "extends", "false", "finally", "for", "function", "if", "import", "in", "instanceof", "let",
+    "new", "null", "of", "return", "static", "super", "switch", "this", "throw", "true", "try", "typeof",
+    "undefined", "var", "void", "while", "with", "yield",
+];
+
+/// TypeScript 在 JavaScript 之外的关键字
+const TYPESCRIPT_KEYWORDS: &[&str] = &[
+    "abstract", "any", "as", "boolean", "declare", "enum", "implements", "interface", "keyof", "namespace", "never",
+    "number", "private", "protected", "public", "readonly", "string", "type", "unknown",
+];
+
+const JAVA_KEYWORDS: &[&str] = &[
+    "abstract", "boolean", "break", "byte", "case", "catch", "char", "class", "continue", "default", "do", "double",
+    "else", "enum", "extends", "false", "final", "finally", "float", "for", "if", "implements", "import",
+    "instanceof", "int", "interface", "long", "new", "null", "package", "private", "protected", "public", "return",
+    "short", "static", "super", "switch", "synchronized", "this", "throw", "throws", "true", "try", "var", "void",
+    "volatile", "while",
+];
+
+const C_KEYWORDS: &[&str] = &[
+    "auto", "bool", "break", "case", "char", "class", "const", "continue", "default", "delete", "do", "double",
+    "else", "enum", "extern", "false", "float", "for", "goto", "if", "include", "define", "inline", "int", "long",
+    "namespace", "new", "nullptr", "private", "protected", "public", "register", "return", "short", "signed",
+    "sizeof", "static", "struct", "switch", "template", "this", "true", "typedef", "union", "unsigned", "using",
+    "virtual", "void", "volatile", "while",
+];
+
+/// SQL 关键字不区分大小写，这里统一为大写
+const SQL_KEYWORDS: &[&str] = &[
+    "ALTER", "AND", "AS", "ASC", "BETWEEN", "BY", "CASE", "CREATE", "DELETE", "DESC", "DISTINCT", "DROP", "ELSE",
+    "END", "EXISTS", "FROM", "GROUP", "HAVING", "IN", "INDEX", "INNER", "INSERT", "INTO", "IS", "JOIN", "KEY",
+    "LEFT", "LIKE", "LIMIT", "NOT", "NULL", "ON", "OR", "ORDER", "OUTER", "PRIMARY", "RIGHT", "SELECT", "SET",
+    "TABLE", "THEN", "UNION", "UPDATE", "VALUES", "VIEW", "WHEN", "WHERE", "WITH",
+];
+
+impl CodeLanguage {
+    /// 按文件扩展名识别语言
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Some(match extension.to_lowercase().as_str() {
+            "rs" => CodeLanguage::Rust,
+            "py" | "pyw" => CodeLanguage::Python,
+            "js" | "jsx" | "mjs" | "cjs" => CodeLanguage::JavaScript,
+            "ts" | "tsx" | "mts" | "cts" => CodeLanguage::TypeScript,
+            "java" => CodeLanguage::Java,
+            "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "hh" => CodeLanguage::C,
+            "sql" => CodeLanguage::Sql,
+            _ => return None,
+        })
+    }
+
+    fn line_comment(self) -> &'static str {
+        match self {
+            CodeLanguage::Python => "#",
+            CodeLanguage::Sql => "--",
+            _ => "//",
+        }
+    }
+
+    fn has_block_comments(self) -> bool {
+        self != CodeLanguage::Python
+    }
+
+    fn quotes(self) -> &'static [char] {
+        match self {
+            CodeLanguage::JavaScript | CodeLanguage::TypeScript => &['"', '\'', '`'],
+            _ => &['"', '\''],
+        }
+    }
+
+    fn is_keyword(self, word: &str) -> bool {
+        match self {
+            CodeLanguage::Rust => RUST_KEYWORDS.contains(&word),
+            CodeLanguage::Python => PYTHON_KEYWORDS.contains(&word),
+            CodeLanguage::JavaScript => JAVASCRIPT_KEYWORDS.contains(&word),
+            CodeLanguage::TypeScript => JAVASCRIPT_KEYWORDS.contains(&word) || TYPESCRIPT_KEYWORDS.contains(&word),
+            CodeLanguage::Java => JAVA_KEYWORDS.contains(&word),
+            CodeLanguage::C => C_KEYWORDS.contains(&word),
+            CodeLanguage::Sql => SQL_KEYWORDS.iter().any(|keyword| keyword.eq_ignore_ascii_case(word)),
+        }
+    }
+}
+
+/// 把源代码切分为首尾相接、覆盖全文的词元
+///
+/// 连续空白为一个词元，注释与字符串字面量整体为一个词元，未闭合的注释或字符串延伸到行尾（块注释延伸到文末）。
+pub fn tokenize(text: &str, language: CodeLanguage) -> Vec<CodeToken> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+
+    while let Some(c) = text[start..].chars().next() {
+        let rest = &text[start..];
+        let (len, kind) = if c.is_whitespace() {
+            (prefix_len(rest, char::is_whitespace), TokenKind::Whitespace)
+        } else if rest.starts_with(language.line_comment()) {
+            (rest.find('\n').unwrap_or(rest.len()), TokenKind::Comment)
+        } else if language.has_block_comments() && rest.starts_with("/*") {
+            (rest[2..].find("*/").map_or(rest.len(), |end| end + 4), TokenKind::Comment)
+        } else if let Some(len) = string_len(rest, language) {
+            (len, TokenKind::String)
+        } else if c.is_ascii_digit() {
+            (prefix_len(rest, |c| c.is_alphanumeric() || c == '_' || c == '.'), TokenKind::Number)
+        } else if is_identifier_char(c, language) {
+            let len = prefix_len(rest, |c| is_identifier_char(c, language));
+            let kind = if language.is_keyword(&rest[..len]) {
+                TokenKind::Keyword
+            } else {
+                TokenKind::Identifier
+            };
+            (len, kind)
+        } else {
+            let len = OPERATORS
+                .iter()
+                .find(|operator| rest.starts_with(*operator))
+                .map_or(c.len_utf8(), |operator| operator.len());
+            (len, TokenKind::Operator)
+        };
+
+        tokens.push(CodeToken { range: start..start + len, kind });
+        start += len;
+    }
+
+    tokens
+}
+
+/// 注释的区间
+pub fn comment_ranges(text: &str, language: CodeLanguage) -> Vec<Range<usize>> {
+    tokenize(text, language)
+        .into_iter()
+        .filter(|token| token.kind == TokenKind::Comment)
+        .map(|token| token.range)
+        .collect()
+}
+
+fn prefix_len(text: &str, accept: impl Fn(char) -> bool) -> usize {
+    text.find(|c| !accept(c)).unwrap_or(text.len())
+}
+
+fn is_identifier_char(c: char, language: CodeLanguage) -> bool {
+    c.is_alphanumeric()
+        || c == '_'
+        || (c == '$' && matches!(language, CodeLanguage::JavaScript | CodeLanguage::TypeScript | CodeLanguage::Java))
+}
+
+/// 以引号开头的字符串字面量长度；Rust 中不成字符字面量的 `'` 是生命周期标记
+fn string_len(text: &str, language: CodeLanguage) -> Option<usize> {
+    let quote = text.chars().next().filter(|c| language.quotes().contains(c))?;
+
+    // Python 与 Java 的三引号字符串可以跨行
+    let triple = quote.to_string().repeat(3);
+    if matches!(language, CodeLanguage::Python | CodeLanguage::Java) && text.starts_with(&triple) {
+        return Some(text[3..].find(&triple).map_or(text.len(), |end| end + 6));
+    }
+
+    // SQL 中引号内以重复引号转义，其余语言以反斜杠转义；只有模板字符串可以跨行
+    let escapes = language != CodeLanguage::Sql;
+    let multiline = quote == '`';
+    let mut chars = text.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' if escapes => {
+                chars.next();
+            }
+            '\n' if !multiline => break,
+            c if c == quote && !escapes && text[i + 1..].starts_with(quote) => {
+                chars.next();
+            }
+            c if c == quote => {
+                let len = i + c.len_utf8();
+                let is_lifetime = language == CodeLanguage::Rust
+                    && quote == '\''
+                    && text[1..i].chars().count() > 1
+                    && !text[1..].starts_with('\\');
+                return (!is_lifetime).then_some(len);
+            }
+            _ => {}
+        }
+    }
+
+    // 未闭合：Rust 的 `'a` 是生命周期，其余延伸到行尾
+    if language == CodeLanguage::Rust && quote == '\'' {
+        return None;
+    }
+    Some(text.find('\n').unwrap_or(text.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(text: &str, language: CodeLanguage) -> Vec<(&str, TokenKind)> {
+        tokenize(text, language)
+            .into_iter()
+            .filter(|token| token.kind != TokenKind::Whitespace)
+            .map(|token| (&text[token.range], token.kind))
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_languages() {
+        use TokenKind::*;
+
+        assert_eq!(
+            kinds("fn f<'a>(x: &'a str) -> char { 'x' } // 注释", CodeLanguage::Rust),
+            vec![
+                ("fn", Keyword), ("f", Identifier), ("<", Operator), ("'", Operator), ("a", Identifier), (">", Operator),
+                ("(", Operator), ("x", Identifier), (":", Operator), ("&", Operator), ("'", Operator), ("a", Identifier),
+                ("str", Identifier), (")", Operator), ("->", Operator), ("char", Identifier), ("{", Operator),
+                ("'x'", String), ("}", Operator), ("// 注释", Comment),
+            ]
+        );
+        assert_eq!(
+            kinds("def f(): return \"\"\"a\nb\"\"\" # x", CodeLanguage::Python),
+            vec![("def", Keyword), ("f", Identifier), ("(", Operator), (")", Operator), (":", Operator), ("return", Keyword), ("\"\"\"a\nb\"\"\"", String), ("# x", Comment)]
+        );
+        assert_eq!(
+            kinds("select 'it''s' /* c */ from t -- x", CodeLanguage::Sql),
+            vec![("select", Keyword), ("'it''s'", String), ("/* c */", Comment), ("from", Keyword), ("t", Identifier), ("-- x", Comment)]
+        );
+
+        // 词元首尾相接覆盖全文
+        let text = "const s = `a\n${b}` === 1.5e3;";
+        let tokens = tokenize(text, CodeLanguage::JavaScript);
+        assert_eq!(tokens.iter().map(|token| &text[token.range.clone()]).collect::<std::string::String>(), text);
+        assert!(tokens.iter().any(|token| &text[token.range.clone()] == "===" && token.kind == Operator));
+    }
+
+    #[test]
+    fn test_code_diff_respects_tokens_and_ignores_comments() {
+        use crate::diff_engine::{DiffEngine, DiffOptions, DiffType};
+
+        let options = DiffOptions {
+            code_language: Some(CodeLanguage::Rust),
+            ..Default::default()
+        };
+        let engine = DiffEngine::new(options.clone());
+        let result = engine.compute_diff("let count = 1;", "let counter = 1;");
+        let changes: Vec<_> = result.items.iter().filter(|item| item.diff_type != DiffType::Equal).collect();
+        assert_eq!(changes.len(), 1);
+        assert_eq!((changes[0].original_content.as_deref(), changes[0].content.as_str()), (Some("count"), "counter"));
+
+        // 只改了注释与缩进
+        let left = "fn main() {\n    run(); // 旧注释\n}\n";
+        let right = "fn main() {\n  run(); /* 新注释 */\n}\n";
+        let engine = DiffEngine::new(DiffOptions { ignore_comments: true, ignore_whitespace: true, ..options });
+        assert_eq!(engine.compute_diff(left, right).stats.total_changes, 0);
+    }
+}
@@ -0,0 +1,96 @@
+// 长时间对比的控制：取消标记、进度回调与对比期限
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DiffError {
+    #[error("对比已取消")]
+    Cancelled,
+    #[error("无效的规则 {0}")]
+    InvalidPattern(String),
+    #[error("无效的规则配置: {0}")]
+    InvalidProfile(String),
+    #[error("无法解析的结构化文档 {0}")]
+    InvalidDocument(String),
+    #[error("表格中没有列 {0}")]
+    MissingColumn(String),
+}
+
+/// 取消标记，克隆后共享同一状态，可在其他线程中取消
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// 对比进度：已处理与总计的文本长度（两侧字节数之和）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiffProgress {
+    pub processed: usize,
+    pub total: usize,
+}
+
+/// 进度回调，并行对比时可能在多个线程中调用
+pub type ProgressCallback = Arc<dyn Fn(DiffProgress) + Send + Sync>;
+
+/// 对比期限：算法在期限后放弃求最优解，并记录期限已被触及
+///
+/// 关联了取消标记时，取消后同样视为到期，各算法在计算途中即可停止。
+#[derive(Debug, Default)]
+pub struct Deadline {
+    at: Option<Instant>,
+    cancellation: Option<CancellationToken>,
+    reached: AtomicBool,
+}
+
+impl Deadline {
+    pub fn never() -> Self {
+        Self::default()
+    }
+
+    pub fn after(timeout: Duration) -> Self {
+        Self {
+            at: Some(Instant::now() + timeout),
+            ..Self::default()
+        }
+    }
+
+    /// 关联取消标记
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// 是否已过期限或已取消；因期限过期时记录下来
+    pub fn expired(&self) -> bool {
+        if self.cancellation.as_ref().is_some_and(CancellationToken::is_cancelled) {
+            return true;
+        }
+
+        let expired = self.at.is_some_and(|at| Instant::now() >= at);
+        if expired {
+            self.reached.store(true, Ordering::Relaxed);
+        }
+        expired
+    }
+
+    /// 是否有算法因期限而提前结束
+    pub fn reached(&self) -> bool {
+        self.reached.load(Ordering::Relaxed)
+    }
+}
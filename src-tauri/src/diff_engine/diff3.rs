@@ -0,0 +1,266 @@
+// 三方对比与合并：以共同基线为参照，区分单侧修改、双方相同修改与冲突
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use super::ops::DiffOp;
+use super::position::TextCursor;
+use super::TextRange;
+
+/// 三方对比块的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Diff3ChunkType {
+    /// 三方一致
+    Unchanged,
+    /// 仅左侧修改
+    LeftOnly,
+    /// 仅右侧修改
+    RightOnly,
+    /// 两侧做了相同的修改
+    BothSame,
+    /// 两侧修改不同
+    Conflict,
+}
+
+/// 三方对比块，三侧文本均为原文
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diff3Chunk {
+    pub id: String,
+    pub chunk_type: Diff3ChunkType,
+    pub base: String,
+    pub left: String,
+    pub right: String,
+    pub base_range: TextRange,
+    pub left_range: TextRange,
+    pub right_range: TextRange,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Diff3Stats {
+    pub left_only: usize,
+    pub right_only: usize,
+    pub both_same: usize,
+    pub conflicts: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Diff3Result {
+    pub chunks: Vec<Diff3Chunk>,
+    pub stats: Diff3Stats,
+}
+
+/// 三方合并结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeResult {
+    /// 合并后的文本，冲突处带冲突标记
+    pub merged_text: String,
+    pub has_conflicts: bool,
+    pub stats: Diff3Stats,
+}
+
+/// 冲突标记
+const MARKER_LEFT: &str = "<<<<<<< 左侧";
+const MARKER_BASE: &str = "||||||| 基线";
+const MARKER_SEPARATOR: &str = "=======";
+const MARKER_RIGHT: &str = ">>>>>>> 右侧";
+
+/// 参与三方对比的一侧：原文及其比较单元的字节区间
+pub struct Side<'a> {
+    pub text: &'a str,
+    pub units: &'a [Range<usize>],
+}
+
+impl Side<'_> {
+    /// 单元下标区间 → 原文字节区间；空区间定位到下一个单元的起点
+    fn byte_range(&self, units: &Range<usize>) -> Range<usize> {
+        if units.is_empty() {
+            let at = self
+                .units
+                .get(units.start)
+                .map_or(self.text.len(), |unit| unit.start);
+            at..at
+        } else {
+            self.units[units.start].start..self.units[units.end - 1].end
+        }
+    }
+}
+
+/// 计算三方对比块
+///
+/// `left_ops`、`right_ops` 分别为基线到左侧、右侧的编辑操作；
+/// `same` 判断两侧修改后的单元（左侧、右侧的单元下标区间）是否视为相同。
+pub fn diff3(
+    base: Side,
+    left: Side,
+    right: Side,
+    left_ops: &[DiffOp],
+    right_ops: &[DiffOp],
+    same: impl Fn(Range<usize>, Range<usize>) -> bool,
+) -> Diff3Result {
+    let base_len = base.units.len();
+    let (left_len, right_len) = (left.units.len(), right.units.len());
+    let left_match = matches(left_ops, base_len);
+    let right_match = matches(right_ops, base_len);
+
+    let mut builder = ChunkBuilder {
+        base: &base,
+        left: &left,
+        right: &right,
+        base_cursor: TextCursor::new(base.text),
+        left_cursor: TextCursor::new(left.text),
+        right_cursor: TextCursor::new(right.text),
+        result: Diff3Result {
+            chunks: Vec::new(),
+            stats: Diff3Stats::default(),
+        },
+    };
+
+    let (mut i, mut j, mut k) = (0, 0, 0);
+    loop {
+        // 稳定区：基线单元同时与两侧对应
+        let start = (i, j, k);
+        while i < base_len && left_match[i] == Some(j) && right_match[i] == Some(k) {
+            i += 1;
+            j += 1;
+            k += 1;
+        }
+        if (i, j, k) != start {
+            builder.push(Diff3ChunkType::Unchanged, start.0..i, start.1..j, start.2..k);
+        }
+
+        if i >= base_len && j >= left_len && k >= right_len {
+            break;
+        }
+
+        // 不稳定区：延伸到下一个与两侧都对应的基线单元
+        let (next_i, next_j, next_k) = (i..base_len)
+            .find_map(|x| Some((x, left_match[x]?, right_match[x]?)))
+            .unwrap_or((base_len, left_len, right_len));
+
+        let unchanged = |matched: &[Option<usize>], from: usize, to: usize| {
+            to - from == next_i - i && (i..next_i).all(|x| matched[x] == Some(from + x - i))
+        };
+        let left_changed = !unchanged(&left_match, j, next_j);
+        let right_changed = !unchanged(&right_match, k, next_k);
+
+        let chunk_type = match (left_changed, right_changed) {
+            (true, false) => Diff3ChunkType::LeftOnly,
+            (false, true) => Diff3ChunkType::RightOnly,
+            (true, true) => {
+                if same(j..next_j, k..next_k) {
+                    Diff3ChunkType::BothSame
+                } else {
+                    Diff3ChunkType::Conflict
+                }
+            }
+            (false, false) => Diff3ChunkType::Unchanged,
+        };
+        builder.push(chunk_type, i..next_i, j..next_j, k..next_k);
+
+        (i, j, k) = (next_i, next_j, next_k);
+    }
+
+    builder.result
+}
+
+/// 基线单元 → 对应的另一侧单元下标
+fn matches(ops: &[DiffOp], base_len: usize) -> Vec<Option<usize>> {
+    let mut matched = vec![None; base_len];
+
+    for op in ops {
+        if let DiffOp::Equal { old_index, new_index, len } = *op {
+            for offset in 0..len {
+                matched[old_index + offset] = Some(new_index + offset);
+            }
+        }
+    }
+
+    matched
+}
+
+struct ChunkBuilder<'a> {
+    base: &'a Side<'a>,
+    left: &'a Side<'a>,
+    right: &'a Side<'a>,
+    base_cursor: TextCursor<'a>,
+    left_cursor: TextCursor<'a>,
+    right_cursor: TextCursor<'a>,
+    result: Diff3Result,
+}
+
+impl ChunkBuilder<'_> {
+    fn push(
+        &mut self,
+        chunk_type: Diff3ChunkType,
+        base_units: Range<usize>,
+        left_units: Range<usize>,
+        right_units: Range<usize>,
+    ) {
+        let base_bytes = self.base.byte_range(&base_units);
+        let left_bytes = self.left.byte_range(&left_units);
+        let right_bytes = self.right.byte_range(&right_units);
+
+        let stats = &mut self.result.stats;
+        match chunk_type {
+            Diff3ChunkType::Unchanged => {}
+            Diff3ChunkType::LeftOnly => stats.left_only += 1,
+            Diff3ChunkType::RightOnly => stats.right_only += 1,
+            Diff3ChunkType::BothSame => stats.both_same += 1,
+            Diff3ChunkType::Conflict => stats.conflicts += 1,
+        }
+
+        let id = format!("chunk-{}", self.result.chunks.len());
+        self.result.chunks.push(Diff3Chunk {
+            id,
+            chunk_type,
+            base: self.base.text[base_bytes.clone()].to_string(),
+            left: self.left.text[left_bytes.clone()].to_string(),
+            right: self.right.text[right_bytes.clone()].to_string(),
+            base_range: self.base_cursor.range(base_bytes.start, base_bytes.end),
+            left_range: self.left_cursor.range(left_bytes.start, left_bytes.end),
+            right_range: self.right_cursor.range(right_bytes.start, right_bytes.end),
+        });
+    }
+}
+
+impl Diff3Result {
+    /// 合并两侧修改：单侧修改取修改方，相同修改取任一侧，冲突处插入冲突标记
+    pub fn merge(&self) -> MergeResult {
+        let mut merged_text = String::new();
+
+        for chunk in &self.chunks {
+            match chunk.chunk_type {
+                Diff3ChunkType::Unchanged | Diff3ChunkType::LeftOnly | Diff3ChunkType::BothSame => {
+                    merged_text.push_str(&chunk.left);
+                }
+                Diff3ChunkType::RightOnly => merged_text.push_str(&chunk.right),
+                Diff3ChunkType::Conflict => {
+                    // 冲突标记独占一行
+                    if !merged_text.is_empty() && !merged_text.ends_with('\n') {
+                        merged_text.push('\n');
+                    }
+                    for (marker, text) in [
+                        (MARKER_LEFT, chunk.left.as_str()),
+                        (MARKER_BASE, chunk.base.as_str()),
+                        (MARKER_SEPARATOR, chunk.right.as_str()),
+                    ] {
+                        merged_text.push_str(marker);
+                        merged_text.push('\n');
+                        merged_text.push_str(text);
+                        if !text.is_empty() && !text.ends_with('\n') {
+                            merged_text.push('\n');
+                        }
+                    }
+                    merged_text.push_str(MARKER_RIGHT);
+                    merged_text.push('\n');
+                }
+            }
+        }
+
+        MergeResult {
+            merged_text,
+            has_conflicts: self.stats.conflicts > 0,
+            stats: self.stats.clone(),
+        }
+    }
+}
@@ -0,0 +1,249 @@
+// Histogram Diff 算法实现（参考 JGit / git diff --histogram）
+//
+// 以左侧出现次数最少的公共元素为锚点，向两侧扩展成最长公共区间，再分别处理区间两侧。
+// 对代码类文本（大量重复的括号、空行）比 Myers 更容易得到符合直觉的结果。
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::ops::Range;
+
+use super::control::Deadline;
+use super::myers;
+use super::ops::{DiffOp, OpsBuilder};
+
+/// 出现次数超过该值的元素不作为锚点
+const MAX_CHAIN_LEN: usize = 64;
+
+/// 锚点左侧子区间的最大递归深度，超过后改用 Myers
+const MAX_DEPTH: usize = 64;
+
+/// 在期限前执行 Histogram Diff；期限到后不再寻找锚点，剩余部分交给同样受期限约束的 Myers
+///
+/// 只在一侧出现的元素不可能配对，先剔除后对比，再把结果映射回原序列。
+pub fn diff_before<T: Hash + Eq>(old: &[T], new: &[T], deadline: &Deadline) -> Vec<DiffOp> {
+    let old_set: HashSet<&T> = old.iter().collect();
+    let new_set: HashSet<&T> = new.iter().collect();
+    let old_kept: Vec<usize> = (0..old.len()).filter(|&i| new_set.contains(&old[i])).collect();
+    let new_kept: Vec<usize> = (0..new.len()).filter(|&j| old_set.contains(&new[j])).collect();
+    let old_elements: Vec<&T> = old_kept.iter().map(|&i| &old[i]).collect();
+    let new_elements: Vec<&T> = new_kept.iter().map(|&j| &new[j]).collect();
+
+    let mut kept = OpsBuilder::new();
+    diff_range(
+        &old_elements,
+        0..old_elements.len(),
+        &new_elements,
+        0..new_elements.len(),
+        0,
+        deadline,
+        &mut kept,
+    );
+
+    // 相等的元素换算回原序列下标，其间被剔除的元素为删除与插入
+    let mut builder = OpsBuilder::new();
+    let (mut i, mut j) = (0, 0);
+    for op in kept.finish() {
+        if let DiffOp::Equal { old_index, new_index, len } = op {
+            for k in 0..len {
+                let (old_pos, new_pos) = (old_kept[old_index + k], new_kept[new_index + k]);
+                builder.delete(i, old_pos - i, j);
+                builder.insert(old_pos, j, new_pos - j);
+                builder.equal(old_pos, new_pos, 1);
+                (i, j) = (old_pos + 1, new_pos + 1);
+            }
+        }
+    }
+    builder.delete(i, old.len() - i, j);
+    builder.insert(old.len(), j, new.len() - j);
+
+    builder.finish()
+}
+
+/// 最佳公共区间
+struct Region {
+    old_start: usize,
+    new_start: usize,
+    len: usize,
+    // 区间内元素在左侧出现次数的最小值，越小越适合作为锚点
+    min_count: usize,
+}
+
+fn diff_range<T: Hash + Eq>(
+    old: &[T],
+    mut old_range: Range<usize>,
+    new: &[T],
+    mut new_range: Range<usize>,
+    depth: usize,
+    deadline: &Deadline,
+    builder: &mut OpsBuilder,
+) {
+    // 公共后缀（最后再输出）；之后各轮的剩余区间与此同尾，无需再去
+    let mut suffix_len = 0;
+    while !old_range.is_empty() && !new_range.is_empty() && old[old_range.end - 1] == new[new_range.end - 1] {
+        old_range.end -= 1;
+        new_range.end -= 1;
+        suffix_len += 1;
+    }
+
+    // 锚点左侧递归，右侧的剩余区间在循环中继续处理，交错插入时递归深度不随序列长度增长
+    loop {
+        // 公共前缀
+        while !old_range.is_empty() && !new_range.is_empty() && old[old_range.start] == new[new_range.start] {
+            builder.equal(old_range.start, new_range.start, 1);
+            old_range.start += 1;
+            new_range.start += 1;
+        }
+
+        if old_range.is_empty() {
+            builder.insert(old_range.start, new_range.start, new_range.len());
+            break;
+        }
+        if new_range.is_empty() {
+            builder.delete(old_range.start, old_range.len(), new_range.start);
+            break;
+        }
+
+        let region = if depth < MAX_DEPTH && !deadline.expired() {
+            find_region(old, old_range.clone(), new, new_range.clone())
+        } else {
+            None
+        };
+        let Some(region) = region else {
+            myers::diff_range(old, old_range.clone(), new, new_range.clone(), deadline, builder);
+            break;
+        };
+
+        diff_range(
+            old,
+            old_range.start..region.old_start,
+            new,
+            new_range.start..region.new_start,
+            depth + 1,
+            deadline,
+            builder,
+        );
+        builder.equal(region.old_start, region.new_start, region.len);
+        old_range.start = region.old_start + region.len;
+        new_range.start = region.new_start + region.len;
+    }
+
+    builder.equal(old_range.end, new_range.end, suffix_len);
+}
+
+fn find_region<T: Hash + Eq>(
+    old: &[T],
+    old_range: Range<usize>,
+    new: &[T],
+    new_range: Range<usize>,
+) -> Option<Region> {
+    // 左侧直方图：元素 → 出现位置
+    let mut histogram: HashMap<&T, Vec<usize>> = HashMap::new();
+    for i in old_range.clone() {
+        histogram.entry(&old[i]).or_default().push(i);
+    }
+
+    let mut best: Option<Region> = None;
+    let mut new_pos = new_range.start;
+
+    while new_pos < new_range.end {
+        let mut next_new_pos = new_pos + 1;
+
+        let positions = histogram
+            .get(&new[new_pos])
+            .filter(|positions| positions.len() <= MAX_CHAIN_LEN);
+
+        if let Some(positions) = positions {
+            for &old_pos in positions {
+                let (mut old_start, mut new_start) = (old_pos, new_pos);
+                while old_start > old_range.start
+                    && new_start > new_range.start
+                    && old[old_start - 1] == new[new_start - 1]
+                {
+                    old_start -= 1;
+                    new_start -= 1;
+                }
+
+                let (mut old_end, mut new_end) = (old_pos + 1, new_pos + 1);
+                while old_end < old_range.end && new_end < new_range.end && old[old_end] == new[new_end] {
+                    old_end += 1;
+                    new_end += 1;
+                }
+
+                let min_count = (old_start..old_end)
+                    .map(|i| histogram.get(&old[i]).map_or(usize::MAX, Vec::len))
+                    .min()
+                    .unwrap_or(usize::MAX);
+
+                let len = old_end - old_start;
+                let better = match &best {
+                    None => true,
+                    Some(current) => {
+                        min_count < current.min_count
+                            || (min_count == current.min_count && len > current.len)
+                    }
+                };
+
+                if better {
+                    best = Some(Region {
+                        old_start,
+                        new_start,
+                        len,
+                        min_count,
+                    });
+                }
+
+                // 已覆盖的右侧区间无需再逐个检查
+                next_new_pos = next_new_pos.max(new_end);
+            }
+        }
+
+        new_pos = next_new_pos;
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 由编辑操作还原右侧序列
+    fn apply<T: Clone>(old: &[T], new: &[T], ops: &[DiffOp]) -> Vec<T> {
+        let mut result = Vec::new();
+        for op in ops {
+            match *op {
+                DiffOp::Equal { old_index, len, .. } => result.extend_from_slice(&old[old_index..old_index + len]),
+                DiffOp::Insert { new_index, new_len, .. } => result.extend_from_slice(&new[new_index..new_index + new_len]),
+                DiffOp::Delete { .. } => {}
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_interleaved_insertions_do_not_overflow_stack() {
+        // 每行之前都插入一行新内容：所有原有行保持相等
+        let old: Vec<u32> = (0..100_000).collect();
+        let new: Vec<u32> = old.iter().flat_map(|&line| [line + 100_000, line]).collect();
+        let ops = diff_before(&old, &new, &Deadline::never());
+        assert_eq!(ops.len(), 200_000);
+        assert_eq!(apply(&old, &new, &ops), new);
+
+        // 插入的行在左侧也多次出现、不能剔除时，逐个锚点处理也不加深递归
+        let old: Vec<u32> = (0..2_000).chain([u32::MAX; MAX_CHAIN_LEN + 1]).collect();
+        let new: Vec<u32> = (0..2_000).flat_map(|line| [u32::MAX, line]).collect();
+        let ops = std::thread::Builder::new()
+            .stack_size(64 * 1024)
+            .spawn(move || diff_before(&old, &new, &Deadline::never()))
+            .unwrap()
+            .join()
+            .unwrap();
+        let equal: usize = ops
+            .iter()
+            .map(|op| match op {
+                DiffOp::Equal { len, .. } => *len,
+                _ => 0,
+            })
+            .sum();
+        assert_eq!(equal, 2_000);
+    }
+}
@@ -0,0 +1,55 @@
+// 经典 LCS 动态规划算法
+//
+// 需要 O(m·n) 的表，只适合短序列；超过上限时改用结果等价的线性空间 Myers 算法。
+use super::control::Deadline;
+use super::myers;
+use super::ops::{DiffOp, OpsBuilder};
+
+/// LCS 表允许的最大单元格数（约 64MB）
+const MAX_TABLE_CELLS: usize = 16 * 1024 * 1024;
+
+/// 在期限前执行 LCS Diff，超过期限时两侧整体视为删除加插入
+pub fn diff_before<T: Eq>(old: &[T], new: &[T], deadline: &Deadline) -> Vec<DiffOp> {
+    let m = old.len();
+    let n = new.len();
+
+    if (m + 1).saturating_mul(n + 1) > MAX_TABLE_CELLS {
+        return myers::diff_before(old, new, deadline);
+    }
+
+    let mut builder = OpsBuilder::new();
+
+    // table[i][j] 为 old[i..] 与 new[j..] 的 LCS 长度
+    let mut table = vec![vec![0u32; n + 1]; m + 1];
+    for i in (0..m).rev() {
+        if deadline.expired() {
+            builder.delete(0, m, 0);
+            builder.insert(m, 0, n);
+            return builder.finish();
+        }
+        for j in (0..n).rev() {
+            table[i][j] = if old[i] == new[j] {
+                table[i + 1][j + 1] + 1
+            } else {
+                table[i + 1][j].max(table[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < m || j < n {
+        if i < m && j < n && old[i] == new[j] {
+            builder.equal(i, j, 1);
+            i += 1;
+            j += 1;
+        } else if j < n && (i == m || table[i][j + 1] > table[i + 1][j]) {
+            builder.insert(i, j, 1);
+            j += 1;
+        } else {
+            builder.delete(i, 1, j);
+            i += 1;
+        }
+    }
+
+    builder.finish()
+}
@@ -1,13 +1,62 @@
 // Diff引擎核心模块
+use std::borrow::Cow;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+mod algorithm;
+mod builder;
+mod code;
+mod control;
+mod diff3;
+mod histogram;
+mod lcs;
+mod moves;
+mod myers;
+mod normalize;
+mod ops;
+mod partition;
+mod patch;
+mod patience;
+mod position;
+mod review;
+mod rules;
+mod side_by_side;
+mod stats;
+mod stream;
+mod structured;
+mod table;
+mod tokenizer;
+
+pub use algorithm::SequenceDiff;
+pub use code::{tokenize as tokenize_code, CodeLanguage, CodeToken, TokenKind};
+pub use control::{CancellationToken, Deadline, DiffError, DiffProgress, ProgressCallback};
+pub use diff3::{Diff3Chunk, Diff3ChunkType, Diff3Result, Diff3Stats, MergeResult};
+pub use patch::{apply_patch, apply_patch_detailed, AppliedHunk, AppliedPatch, PatchError, PatchOptions};
+pub use review::{ReviewDecision, ReviewError, ReviewProgress, ReviewSession};
+pub use rules::{EquivalenceRule, RuleProfile};
+pub use side_by_side::{align_rows, source_texts, AlignedRow, Highlight, RowCell, SideBySide};
+pub use stream::DiffStream;
+pub use structured::StructuredFormat;
+pub use table::{Table, TableCell, TableColumn, TableDiff, TableRow, TableStats};
+pub use tokenizer::Tokenizer;
+
+use builder::Pairing;
+use normalize::Normalizer;
+use rules::CompiledRules;
+use stats::StatsBuilder;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DiffType {
     Add,
     Remove,
     Modify,
     Equal,
+    /// 移动：`position.left` 为来源位置，`position.right` 为目标位置
+    Move,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,111 +64,554 @@ pub struct DiffItem {
     pub id: String,
     pub diff_type: DiffType,
     pub content: String,
+    /// 左侧原文；相等项仅在两侧原文不同（忽略选项生效）时给出
     pub original_content: Option<String>,
     pub line_number: Option<usize>,
     pub position: Position,
+    /// 修改项内部的下一层级差异（段落内为句子，句内为词），位置为全文坐标
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inline_changes: Vec<DiffItem>,
+    /// 行级对比中该项覆盖的左侧行
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub left_line: Option<LineRange>,
+    /// 行级对比中该项覆盖的右侧行
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub right_line: Option<LineRange>,
+    /// 结构化对比中该项的 JSON Pointer 路径
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// 差异项在两侧原文中的位置
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Position {
+    pub left: TextRange,
+    pub right: TextRange,
+}
+
+/// 行区间：行号从1开始，左闭右开；空区间的起点为插入位置
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineRange {
     pub start: usize,
     pub end: usize,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl LineRange {
+    /// 文本区间覆盖的行，区间须由整行组成
+    fn covering(range: &TextRange) -> Self {
+        if range.byte_start == range.byte_end {
+            // 末行没有换行符时，文本末尾的插入位置在末行之后
+            let start = range.start_line + usize::from(range.start_column > 1);
+            return Self { start, end: start };
+        }
+        
+        let end = range.end_line + usize::from(range.end_column > 1);
+        Self { start: range.start_line, end }
+    }
+    
+    /// 平移到从 `first_line` 行开始的区间中
+    fn offset_by(self, first_line: usize) -> Self {
+        Self {
+            start: self.start + first_line - 1,
+            end: self.end + first_line - 1,
+        }
+    }
+}
+
+/// 文本区间：字节与字符偏移均为左闭右开，行号与列号从1开始
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextRange {
+    pub byte_start: usize,
+    pub byte_end: usize,
+    pub char_start: usize,
+    pub char_end: usize,
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DiffOptions {
     pub ignore_case: bool,
     pub ignore_whitespace: bool,
     pub ignore_punctuation: bool,
+    /// 按段落（空行分隔）对比，段落内再按句、句内按词逐层细化
     pub split_by_paragraph: bool,
     pub split_by_sentence: bool,
+    /// 按词对比（拉丁文按词边界，中文按词典分词）
+    #[serde(default)]
+    pub split_by_word: bool,
+    /// 按行对比，差异项带两侧行号区间，修改的行内再做词级对比（适合配置文件、日志与代码）
+    #[serde(default)]
+    pub split_by_line: bool,
     pub use_web_worker: bool,
+    /// 指定对比算法；为空时句子级使用 Patience，行级与代码使用 Histogram，词级与字符级使用 Myers
+    #[serde(default)]
+    pub algorithm: Option<DiffAlgorithm>,
+    /// Unicode 规范化形式，为空时不做规范化
+    #[serde(default)]
+    pub unicode_form: Option<UnicodeForm>,
+    /// 全角字母、数字、标点与全角空格折叠为半角后再比较
+    #[serde(default)]
+    pub fold_width: bool,
+    /// 繁体中文折叠为简体后再比较
+    #[serde(default)]
+    pub fold_chinese_variants: bool,
+    /// 移动块判定的相似度阈值（0~1），为空时取 0.8；设为大于 1 的值可关闭移动检测
+    #[serde(default)]
+    pub move_threshold: Option<f32>,
+    /// 大文本按两侧相同的唯一行切分为独立区间，多线程并行对比
+    #[serde(default)]
+    pub parallel: bool,
+    /// 对比期限（毫秒）；超过后退回按行（段落模式下按段落）的粗粒度对比，结果标记为近似
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// 忽略规则：匹配这些正则的文本不参与比较（如日期、版本号）
+    #[serde(default)]
+    pub ignore_patterns: Vec<String>,
+    /// 忽略重新折行：软换行（前后两行都有内容）视为空格，中文之间的软换行直接连接
+    #[serde(default)]
+    pub ignore_reflow: bool,
+    /// 忽略行首的列表编号（1.、(2)、三、等），重新编号不视为改动
+    #[serde(default)]
+    pub ignore_list_numbering: bool,
+    /// 等价规则：匹配的文本改写为规范形式后再比较（如 "Ltd." 与 "Limited"、"第一条" 与 "第1条"）
+    #[serde(default)]
+    pub equivalence_rules: Vec<EquivalenceRule>,
+    /// 代码对比：按该语言的词法单元（关键字、标识符、字面量、注释、运算符）对比；按行对比时仍按行
+    #[serde(default)]
+    pub code_language: Option<CodeLanguage>,
+    /// 代码对比中注释不参与比较
+    #[serde(default)]
+    pub ignore_comments: bool,
+    /// 结构化对比：两侧按该格式解析为树，按键路径对比，键的顺序不计
+    #[serde(default)]
+    pub structured: Option<StructuredFormat>,
+    /// 结构化对比中数组元素（对象）的配对字段，为空时取 "id"
+    #[serde(default)]
+    pub array_id_key: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl DiffOptions {
+    /// 检查忽略规则与等价规则中的正则
+    pub fn validate(&self) -> Result<(), DiffError> {
+        CompiledRules::compile(self).map(|_| ())
+    }
+}
+
+/// Unicode 规范化形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnicodeForm {
+    /// 标准等价合成
+    Nfc,
+    /// 兼容等价合成，会折叠全角字母、上标、合字等
+    Nfkc,
+}
+
+/// 可选的对比算法
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DiffAlgorithm {
+    /// Myers O(ND)，通用且结果最小
+    #[default]
+    Myers,
+    /// Patience，以唯一行/句为锚点，适合散文类文本
+    Patience,
+    /// Histogram，以低频元素为锚点，适合代码类文本
+    Histogram,
+    /// 经典 LCS 动态规划，仅适合短文本
+    LCS,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DiffStats {
     pub total_changes: usize,
     pub additions: usize,
     pub deletions: usize,
     pub modifications: usize,
+    #[serde(default)]
+    pub moves: usize,
+    /// 新增字词数，每个汉字计一个
     pub added_words: usize,
+    /// 删除字词数，每个汉字计一个
     pub deleted_words: usize,
+    /// 相似度（百分比）：两侧相同字符数之和占两侧总字符数的比例
     pub similarity: f32,
+    /// 新增的非空白字符数
+    #[serde(default)]
+    pub added_chars: usize,
+    /// 删除的非空白字符数
+    #[serde(default)]
+    pub deleted_chars: usize,
+    /// 左侧有改动的行数
+    #[serde(default)]
+    pub left_changed_lines: usize,
+    /// 右侧有改动的行数
+    #[serde(default)]
+    pub right_changed_lines: usize,
+    /// 按对比结果计算的字符级编辑距离（不计移动）
+    #[serde(default)]
+    pub edit_distance: usize,
+    /// Levenshtein 相似度（百分比）：1 - 编辑距离 / 较长一侧字符数
+    #[serde(default)]
+    pub levenshtein_similarity: f32,
+    /// 以相邻两词为词组的 Jaccard 相似度（百分比）
+    #[serde(default)]
+    pub jaccard_similarity: f32,
+}
+
+/// 分层对比的切分层级
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Level {
+    Paragraph,
+    Sentence,
 }
 
+/// 分区对比的最小文本总长度（字节），更短的文本整体对比更快
+const PARTITION_MIN_BYTES: usize = 256 * 1024;
+
 /// 主Diff引擎
 pub struct DiffEngine {
     options: DiffOptions,
+    cancellation: Option<CancellationToken>,
+    progress: Option<ProgressCallback>,
+    deadline: Deadline,
+    /// 编译好的忽略规则与等价规则
+    rules: CompiledRules,
 }
 
 impl DiffEngine {
     pub fn new(options: DiffOptions) -> Self {
-        Self { options }
+        Self {
+            cancellation: None,
+            progress: None,
+            deadline: Deadline::never(),
+            // 无效的正则在 try_compute_diff 中报告，这里直接跳过
+            rules: CompiledRules::compile_lossy(&options),
+            options,
+        }
+    }
+    
+    /// 设置取消标记，取消后尽快结束对比
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.deadline = std::mem::take(&mut self.deadline).with_cancellation(token.clone());
+        self.cancellation = Some(token);
+        self
+    }
+    
+    /// 设置进度回调
+    pub fn with_progress(mut self, callback: impl Fn(DiffProgress) + Send + Sync + 'static) -> Self {
+        self.progress = Some(Arc::new(callback));
+        self
     }
 
     /// 计算两个文本的差异
+    ///
+    /// 设置了 `timeout_ms` 时，超过期限即放弃细粒度结果，改为按行或段落对比，
+    /// 并在元数据中标记为近似结果。
     pub fn compute_diff(&self, left_text: &str, right_text: &str) -> DiffResult {
-        // 预处理文本
-        let processed_left = self.preprocess_text(left_text);
-        let processed_right = self.preprocess_text(right_text);
+        // 结构化文档解析失败时按文本对比
+        let structured = self.options.structured.map(|format| self.structured_diff(format, left_text, right_text));
+        if let Some(Ok(result)) = structured {
+            return result;
+        }
+        
+        let Some(timeout_ms) = self.options.timeout_ms else {
+            return self.run_diff(left_text, right_text);
+        };
+        let timeout = Duration::from_millis(timeout_ms);
+        
+        let timed = self.with_deadline(Deadline::after(timeout));
+        let result = timed.run_diff(left_text, right_text);
+        if !timed.deadline.reached() || self.is_cancelled() {
+            return result;
+        }
         
+        // 粗粒度对比同样受期限约束，再次超时时剩余部分整体视为修改
+        self.with_deadline(Deadline::after(timeout)).coarse_diff(left_text, right_text)
+    }
+    
+    /// 共享选项、取消标记与进度回调，使用新期限的引擎
+    fn with_deadline(&self, deadline: Deadline) -> DiffEngine {
+        let deadline = match &self.cancellation {
+            Some(token) => deadline.with_cancellation(token.clone()),
+            None => deadline,
+        };
+        
+        DiffEngine {
+            options: self.options.clone(),
+            cancellation: self.cancellation.clone(),
+            progress: self.progress.clone(),
+            deadline,
+            rules: self.rules.clone(),
+        }
+    }
+    
+    /// 按选项的粒度对比
+    ///
+    /// 大文本在并行模式下按锚点分区、多线程对比，并在每个区间完成后报告进度；
+    /// 其余情况整体对比，完成时报告一次进度。是否分区只取决于并行选项，
+    /// 设置取消标记或进度回调不会改变结果。取消后返回的结果不完整。
+    fn run_diff(&self, left_text: &str, right_text: &str) -> DiffResult {
+        let total = left_text.len() + right_text.len();
+        
+        if self.options.parallel && total >= PARTITION_MIN_BYTES {
+            partition::partitioned_diff(self, left_text, right_text)
+        } else {
+            let result = self.diff_whole(left_text, right_text);
+            self.report_progress(total, total);
+            result
+        }
+    }
+    
+    /// 可取消的对比：取消时返回 `DiffError::Cancelled`，规则无效时返回 `DiffError::InvalidPattern`，
+    /// 结构化文档无法解析时返回 `DiffError::InvalidDocument`
+    pub fn try_compute_diff(&self, left_text: &str, right_text: &str) -> Result<DiffResult, DiffError> {
+        self.options.validate()?;
+        
+        if self.is_cancelled() {
+            return Err(DiffError::Cancelled);
+        }
+        
+        if let Some(format) = self.options.structured {
+            return self.structured_diff(format, left_text, right_text);
+        }
+        
+        let result = self.compute_diff(left_text, right_text);
+        
+        if self.is_cancelled() {
+            Err(DiffError::Cancelled)
+        } else {
+            Ok(result)
+        }
+    }
+    
+    /// 两侧按结构化格式解析为树后按路径对比，数组元素序列使用所选算法
+    fn structured_diff(&self, format: StructuredFormat, left_text: &str, right_text: &str) -> Result<DiffResult, DiffError> {
+        let (left, right) = (format.parse(left_text)?, format.parse(right_text)?);
+        let algorithm = self.options.algorithm.unwrap_or(DiffAlgorithm::Myers);
+        let tree_diff = structured::TreeDiff {
+            normalizer: self.normalizer(),
+            algorithm: algorithm.implementation(),
+            id_key: self.options.array_id_key.as_deref(),
+        };
+        let (items, stats) = tree_diff.diff(&left, &right);
+        
+        let total = left_text.len() + right_text.len();
+        self.report_progress(total, total);
+        
+        Ok(DiffResult {
+            items,
+            stats,
+            metadata: DiffMetadata {
+                algorithm,
+                has_chinese: self.contains_chinese(left_text) || self.contains_chinese(right_text),
+                approximate: false,
+            },
+        })
+    }
+    
+    fn normalizer(&self) -> Normalizer<'_> {
+        Normalizer::new(&self.options, &self.rules)
+    }
+    
+    fn is_cancelled(&self) -> bool {
+        self.cancellation.as_ref().is_some_and(CancellationToken::is_cancelled)
+    }
+    
+    fn report_progress(&self, processed: usize, total: usize) {
+        if let Some(callback) = &self.progress {
+            callback(DiffProgress { processed, total });
+        }
+    }
+    
+    /// 整体对比两个文本
+    fn diff_whole(&self, left_text: &str, right_text: &str) -> DiffResult {
         // 检测是否包含中文
         let has_chinese = self.contains_chinese(left_text) || self.contains_chinese(right_text);
+        self.diff_part(left_text, right_text, has_chinese)
+    }
+    
+    /// 对比全文中相互对应的一部分，是否按中文分句以全文为准
+    fn diff_part(&self, left_text: &str, right_text: &str, has_chinese: bool) -> DiffResult {
+        let segmented = self.options.split_by_paragraph || self.options.split_by_sentence;
+        let algorithm = self.algorithm();
         
-        let diff_items = if self.options.split_by_paragraph || self.options.split_by_sentence {
-            self.hierarchical_diff(&processed_left, &processed_right, has_chinese)
+        let mut diff_items = if segmented {
+            self.hierarchical_diff(left_text, right_text, has_chinese, algorithm.implementation())
+        } else if self.options.split_by_line {
+            self.line_diff(left_text, right_text, has_chinese, algorithm.implementation())
+        } else if let Some(language) = self.options.code_language {
+            self.code_diff(left_text, right_text, language, algorithm.implementation())
+        } else if self.options.split_by_word {
+            self.word_diff(left_text, right_text, algorithm.implementation())
         } else {
-            self.character_diff(&processed_left, &processed_right, has_chinese)
+            self.character_diff(left_text, right_text, has_chinese, algorithm.implementation())
         };
+        self.detect_moves(&mut diff_items);
         
         let stats = self.calculate_stats(&diff_items, left_text, right_text);
         
         DiffResult {
             items: diff_items,
             stats,
+            metadata: DiffMetadata {
+                algorithm,
+                has_chinese,
+                approximate: false,
+            },
         }
     }
     
-    /// 流式增量对比
-    pub async fn compute_diff_stream(
-        &self,
-        left_text: &str,
-        right_text: &str,
-        chunk_size: usize,
-    ) -> impl futures::Stream<Item = DiffChunk> {
-        use futures::stream::{self, StreamExt};
-        
-        let chunks = self.split_into_chunks(left_text, right_text, chunk_size);
-        
-        stream::iter(chunks).map(move |chunk| {
-            let result = self.compute_diff(&chunk.left, &chunk.right);
-            DiffChunk {
-                index: chunk.index,
-                total: chunk.total,
-                items: result.items,
-                partial_stats: result.stats,
+    /// 粗粒度对比：段落模式下按段落，否则按行，修改项不再细化
+    fn coarse_diff(&self, left_text: &str, right_text: &str) -> DiffResult {
+        let has_chinese = self.contains_chinese(left_text) || self.contains_chinese(right_text);
+        let algorithm = DiffAlgorithm::Patience;
+        let segment = |text: &str| {
+            if self.options.split_by_paragraph {
+                self.segment_paragraphs(text)
+            } else {
+                self.split_after(text, |c| c == '\n')
             }
+        };
+        
+        let (left_units, right_units, ops) = self.diff_units(
+            left_text,
+            &segment(left_text),
+            right_text,
+            &segment(right_text),
+            algorithm.implementation(),
+        );
+        let items = builder::build_items(left_text, &left_units, right_text, &right_units, &ops, Pairing::PerUnit);
+        let stats = self.calculate_stats(&items, left_text, right_text);
+        
+        let total = left_text.len() + right_text.len();
+        self.report_progress(total, total);
+        
+        DiffResult {
+            items,
+            stats,
+            metadata: DiffMetadata {
+                algorithm,
+                has_chinese,
+                approximate: true,
+            },
+        }
+    }
+    
+    /// 实际使用的对比算法
+    fn algorithm(&self) -> DiffAlgorithm {
+        let segmented = self.options.split_by_paragraph || self.options.split_by_sentence;
+        self.options.algorithm.unwrap_or(if segmented {
+            DiffAlgorithm::Patience
+        } else if self.options.split_by_line || self.options.code_language.is_some() {
+            DiffAlgorithm::Histogram
+        } else {
+            DiffAlgorithm::Myers
         })
     }
     
-    fn preprocess_text(&self, text: &str) -> String {
-        let mut processed = text.to_string();
+    /// 三方对比：以 `base` 为共同基线，分别与左右两侧对比后归类每处修改
+    ///
+    /// 比较单元沿用对比选项：按段落或句子切分，否则按行。
+    pub fn compute_diff3(&self, base: &str, left: &str, right: &str) -> Diff3Result {
+        let has_chinese = [base, left, right].iter().any(|text| self.contains_chinese(text));
+        let algorithm = self.options.algorithm.unwrap_or(DiffAlgorithm::Myers).implementation();
+        let segment = |text: &str| {
+            if self.options.split_by_paragraph {
+                self.segment_paragraphs(text)
+            } else if self.options.split_by_sentence {
+                self.segment(text, Level::Sentence, has_chinese)
+            } else {
+                self.split_after(text, |c| c == '\n')
+            }
+        };
         
-        if self.options.ignore_case {
-            processed = processed.to_lowercase();
-        }
+        // 两侧是否改得相同与对比使用同一套比较键（含忽略规则、等价规则）
+        let normalizer = self.normalizer();
+        let (base_units, base_keys) = normalizer.keyed_units(base, &segment(base));
+        let (left_units, left_keys) = normalizer.keyed_units(left, &segment(left));
+        let (right_units, right_keys) = normalizer.keyed_units(right, &segment(right));
         
-        if self.options.ignore_whitespace {
-            processed = processed.split_whitespace().collect::<Vec<_>>().join(" ");
-        }
+        let left_ops = self.diff_keys(&base_keys, &left_keys, algorithm);
+        let right_ops = self.diff_keys(&base_keys, &right_keys, algorithm);
         
-        if self.options.ignore_punctuation {
-            processed = processed.chars()
-                .filter(|c| c.is_alphanumeric() || c.is_whitespace() || self.is_chinese_char(*c))
-                .collect();
+        diff3::diff3(
+            diff3::Side { text: base, units: &base_units },
+            diff3::Side { text: left, units: &left_units },
+            diff3::Side { text: right, units: &right_units },
+            &left_ops,
+            &right_ops,
+            |left_range, right_range| left_keys[left_range] == right_keys[right_range],
+        )
+    }
+    
+    /// 三方合并，冲突处插入冲突标记
+    pub fn merge3(&self, base: &str, left: &str, right: &str) -> MergeResult {
+        self.compute_diff3(base, left, right).merge()
+    }
+    
+    /// 并排视图：对比后按行对齐为左右两栏，改动行配对、缺行以填充行补齐
+    pub fn compute_side_by_side(&self, left_text: &str, right_text: &str) -> SideBySide {
+        let result = self.compute_diff(left_text, right_text);
+        
+        SideBySide {
+            rows: side_by_side::align_rows(&result.items, left_text, right_text),
+            stats: result.stats,
+            metadata: result.metadata,
         }
+    }
+    
+    /// 表格对比：指定键列时按键值配对行，否则按行序列对比；单元格比较沿用忽略选项与等价规则
+    pub fn compute_table_diff(&self, left: &Table, right: &Table, key_column: Option<&str>) -> Result<TableDiff, DiffError> {
+        self.options.validate()?;
+        
+        let algorithm = self.options.algorithm.unwrap_or(DiffAlgorithm::Myers);
+        table::diff_tables(left, right, key_column, &self.normalizer(), algorithm.implementation())
+    }
+    
+    /// 流式增量对比
+    pub fn compute_diff_stream<'a>(
+        &'a self,
+        left_text: &'a str,
+        right_text: &'a str,
+        chunk_size: usize,
+    ) -> impl futures::Stream<Item = DiffChunk> + 'a {
+        futures::stream::iter(self.diff_chunks(left_text, right_text, chunk_size))
+    }
+    
+    /// 逐块对比：以两侧相同的唯一比较单元为锚点切块，每块约 `chunk_size` 个单元（行，分段、分句模式下为段落或句子）
+    pub fn diff_chunks<'a>(&'a self, left_text: &'a str, right_text: &'a str, chunk_size: usize) -> DiffStream<'a> {
+        DiffStream::new(self, left_text, right_text, chunk_size)
+    }
+    
+    /// 对比两侧的比较单元
+    ///
+    /// 忽略选项只作用于比较键：返回的单元区间仍指向原文，
+    /// 被忽略的空白、标点并入相邻单元。
+    fn diff_units(
+        &self,
+        left: &str,
+        left_segments: &[Range<usize>],
+        right: &str,
+        right_segments: &[Range<usize>],
+        algorithm: &dyn SequenceDiff,
+    ) -> (Vec<Range<usize>>, Vec<Range<usize>>, Vec<ops::DiffOp>) {
+        let normalizer = self.normalizer();
+        let (left_units, left_keys) = normalizer.keyed_units(left, left_segments);
+        let (right_units, right_keys) = normalizer.keyed_units(right, right_segments);
+        let ops = self.diff_keys(&left_keys, &right_keys, algorithm);
         
-        processed
+        (left_units, right_units, ops)
+    }
+    
+    /// 对比两侧的比较键序列
+    fn diff_keys(&self, left_keys: &[Cow<str>], right_keys: &[Cow<str>], algorithm: &dyn SequenceDiff) -> Vec<ops::DiffOp> {
+        let left_keys: Vec<&str> = left_keys.iter().map(|key| key.as_ref()).collect();
+        let right_keys: Vec<&str> = right_keys.iter().map(|key| key.as_ref()).collect();
+        
+        let (left_ids, right_ids) = algorithm::intern(&left_keys, &right_keys);
+        algorithm.diff_before(&left_ids, &right_ids, &self.deadline)
     }
     
     fn contains_chinese(&self, text: &str) -> bool {
@@ -127,300 +619,272 @@ impl DiffEngine {
     }
     
     fn is_chinese_char(&self, c: char) -> bool {
-        (c >= '\u{4e00}' && c <= '\u{9fff}') ||
-        (c >= '\u{3400}' && c <= '\u{4dbf}') ||
-        (c >= '\u{20000}' && c <= '\u{2a6df}')
+        tokenizer::is_chinese_char(c)
     }
     
-    fn hierarchical_diff(&self, left: &str, right: &str, has_chinese: bool) -> Vec<DiffItem> {
-        // 双层对比策略实现
-        let segments_left = if has_chinese {
-            self.segment_chinese_text(left)
+    fn hierarchical_diff(
+        &self,
+        left: &str,
+        right: &str,
+        has_chinese: bool,
+        algorithm: &dyn SequenceDiff,
+    ) -> Vec<DiffItem> {
+        // 多层对比策略：段落 → 句子 → 词，逐层细化配对成功的修改项
+        let level = if self.options.split_by_paragraph {
+            Level::Paragraph
         } else {
-            self.segment_text(left)
+            Level::Sentence
         };
         
-        let segments_right = if has_chinese {
-            self.segment_chinese_text(right)
-        } else {
-            self.segment_text(right)
-        };
+        self.segment_diff(left, right, level, has_chinese, algorithm)
+    }
+    
+    /// 位置不同但内容相近的顶层删除块与新增块视为移动，移动中的改动再做词级对比；各文本模式均适用
+    fn detect_moves(&self, items: &mut Vec<DiffItem>) {
+        let threshold = self.options.move_threshold.unwrap_or(moves::DEFAULT_THRESHOLD);
+        let word_algorithm = self.options.algorithm.unwrap_or(DiffAlgorithm::Myers).implementation();
+        
+        for index in moves::detect_moves(items, threshold) {
+            let item = &mut items[index];
+            if item.left_line.is_some() {
+                item.left_line = Some(LineRange::covering(&item.position.left));
+            }
+            
+            let original = item.original_content.as_deref().unwrap_or_default();
+            if original != item.content {
+                let mut inline = self.word_diff(original, &item.content, word_algorithm);
+                adopt_inline_changes(&mut inline, &item.id, &item.position);
+                item.inline_changes = inline;
+            }
+        }
+    }
+    
+    /// 按给定层级切分并对比，再对修改项做下一层对比
+    fn segment_diff(
+        &self,
+        left: &str,
+        right: &str,
+        level: Level,
+        has_chinese: bool,
+        algorithm: &dyn SequenceDiff,
+    ) -> Vec<DiffItem> {
+        let (left_units, right_units, ops) = self.diff_units(
+            left,
+            &self.segment(left, level, has_chinese),
+            right,
+            &self.segment(right, level, has_chinese),
+            algorithm,
+        );
+        
+        let mut items = builder::build_items(left, &left_units, right, &right_units, &ops, Pairing::PerUnit);
+        
+        self.refine_modifications(&mut items, level, has_chinese, algorithm);
         
-        // 使用Patience Diff算法
-        self.patience_diff(&segments_left, &segments_right)
+        items
     }
     
-    fn character_diff(&self, left: &str, right: &str, _has_chinese: bool) -> Vec<DiffItem> {
-        // 字符级对比实现
-        self.lcs_diff(left, right)
+    fn segment(&self, text: &str, level: Level, has_chinese: bool) -> Vec<Range<usize>> {
+        match level {
+            Level::Paragraph => self.segment_paragraphs(text),
+            Level::Sentence if has_chinese => self.segment_chinese_text(text),
+            Level::Sentence => self.segment_text(text),
+        }
     }
     
-    fn segment_chinese_text(&self, text: &str) -> Vec<String> {
-        // 中文分词实现
-        let mut segments = Vec::new();
-        let mut current = String::new();
+    /// 为每个修改项计算下一层级的差异：段落内按句、句内按词
+    fn refine_modifications(
+        &self,
+        items: &mut [DiffItem],
+        level: Level,
+        has_chinese: bool,
+        algorithm: &dyn SequenceDiff,
+    ) {
+        let word_algorithm = self.options.algorithm.unwrap_or(DiffAlgorithm::Myers).implementation();
         
-        for c in text.chars() {
-            if "。！？；".contains(c) {
-                current.push(c);
-                segments.push(current.clone());
-                current.clear();
-            } else {
-                current.push(c);
+        for item in items.iter_mut().filter(|item| item.diff_type == DiffType::Modify) {
+            if self.is_cancelled() {
+                return;
             }
+            
+            let original = item.original_content.as_deref().unwrap_or_default();
+            let mut inline = match level {
+                Level::Paragraph => self.segment_diff(original, &item.content, Level::Sentence, has_chinese, algorithm),
+                Level::Sentence => self.word_diff(original, &item.content, word_algorithm),
+            };
+            
+            adopt_inline_changes(&mut inline, &item.id, &item.position);
+            item.inline_changes = inline;
         }
+    }
+    
+    /// 行级对比：删除与新增的行逐行配对为修改，修改的行内再做词级对比；整行移动识别为移动
+    fn line_diff(
+        &self,
+        left: &str,
+        right: &str,
+        has_chinese: bool,
+        algorithm: &dyn SequenceDiff,
+    ) -> Vec<DiffItem> {
+        let (left_units, right_units, ops) = self.diff_units(
+            left,
+            &self.split_after(left, |c| c == '\n'),
+            right,
+            &self.split_after(right, |c| c == '\n'),
+            algorithm,
+        );
+        let mut items = builder::build_items(left, &left_units, right, &right_units, &ops, Pairing::PerUnit);
+        self.refine_modifications(&mut items, Level::Sentence, has_chinese, algorithm);
         
-        if !current.is_empty() {
-            segments.push(current);
+        for item in &mut items {
+            item.left_line = Some(LineRange::covering(&item.position.left));
+            item.right_line = Some(LineRange::covering(&item.position.right));
         }
         
-        segments
+        items
     }
     
-    fn segment_text(&self, text: &str) -> Vec<String> {
-        text.split_terminator(&['.', '!', '?', ';'][..])
-            .map(|s| s.to_string())
-            .collect()
+    fn word_diff(&self, left: &str, right: &str, algorithm: &dyn SequenceDiff) -> Vec<DiffItem> {
+        // 词级对比：连续被替换的词合并为一个修改项
+        let tokenizer = Tokenizer::builtin();
+        let (left_units, right_units, ops) = self.diff_units(
+            left,
+            &tokenizer.tokenize(left),
+            right,
+            &tokenizer.tokenize(right),
+            algorithm,
+        );
+        
+        builder::build_items(left, &left_units, right, &right_units, &ops, Pairing::Region)
     }
     
-    fn patience_diff(&self, left: &[String], right: &[String]) -> Vec<DiffItem> {
-        // Patience Diff算法实现
-        // 这里是简化版本，实际应使用完整的Patience算法
-        let mut items = Vec::new();
-        let mut left_idx = 0;
-        let mut right_idx = 0;
-        
-        while left_idx < left.len() || right_idx < right.len() {
-            if left_idx >= left.len() {
-                // 右侧有剩余，都是新增
-                items.push(DiffItem {
-                    id: format!("diff-{}", items.len()),
-                    diff_type: DiffType::Add,
-                    content: right[right_idx].clone(),
-                    original_content: None,
-                    line_number: Some(right_idx + 1),
-                    position: Position {
-                        start: right_idx,
-                        end: right_idx + 1,
-                    },
-                });
-                right_idx += 1;
-            } else if right_idx >= right.len() {
-                // 左侧有剩余，都是删除
-                items.push(DiffItem {
-                    id: format!("diff-{}", items.len()),
-                    diff_type: DiffType::Remove,
-                    content: left[left_idx].clone(),
-                    original_content: Some(left[left_idx].clone()),
-                    line_number: Some(left_idx + 1),
-                    position: Position {
-                        start: left_idx,
-                        end: left_idx + 1,
-                    },
-                });
-                left_idx += 1;
-            } else if left[left_idx] == right[right_idx] {
-                // 相同
-                items.push(DiffItem {
-                    id: format!("diff-{}", items.len()),
-                    diff_type: DiffType::Equal,
-                    content: left[left_idx].clone(),
-                    original_content: None,
-                    line_number: Some(left_idx + 1),
-                    position: Position {
-                        start: left_idx,
-                        end: left_idx + 1,
-                    },
-                });
-                left_idx += 1;
-                right_idx += 1;
-            } else {
-                // 不同，检查是修改还是新增/删除
-                items.push(DiffItem {
-                    id: format!("diff-{}", items.len()),
-                    diff_type: DiffType::Modify,
-                    content: right[right_idx].clone(),
-                    original_content: Some(left[left_idx].clone()),
-                    line_number: Some(left_idx + 1),
-                    position: Position {
-                        start: left_idx,
-                        end: left_idx + 1,
-                    },
-                });
-                left_idx += 1;
-                right_idx += 1;
-            }
-        }
+    /// 代码对比：按词元对比，连续被替换的词元合并为一个修改项；忽略空白时只改动格式的部分视为相同
+    fn code_diff(&self, left: &str, right: &str, language: CodeLanguage, algorithm: &dyn SequenceDiff) -> Vec<DiffItem> {
+        let segment = |text: &str| -> Vec<Range<usize>> {
+            code::tokenize(text, language).into_iter().map(|token| token.range).collect()
+        };
+        let (left_units, right_units, ops) = self.diff_units(left, &segment(left), right, &segment(right), algorithm);
         
-        items
+        builder::build_items(left, &left_units, right, &right_units, &ops, Pairing::Region)
     }
     
-    fn lcs_diff(&self, left: &str, right: &str) -> Vec<DiffItem> {
-        // LCS (Longest Common Subsequence) 算法实现
-        let left_chars: Vec<char> = left.chars().collect();
-        let right_chars: Vec<char> = right.chars().collect();
+    fn character_diff(
+        &self,
+        left: &str,
+        right: &str,
+        _has_chinese: bool,
+        algorithm: &dyn SequenceDiff,
+    ) -> Vec<DiffItem> {
+        if !self.normalizer().is_identity() {
+            let (left_units, right_units, ops) = self.diff_units(
+                left,
+                &normalize::segment_clusters(left),
+                right,
+                &normalize::segment_clusters(right),
+                algorithm,
+            );
+            return builder::build_items(left, &left_units, right, &right_units, &ops, Pairing::None);
+        }
+        
+        // 字符级对比：字符本身即为编号，无需驻留
+        let left_chars: Vec<u32> = left.chars().map(u32::from).collect();
+        let right_chars: Vec<u32> = right.chars().map(u32::from).collect();
         
-        let m = left_chars.len();
-        let n = right_chars.len();
+        let ops = algorithm.diff_before(&left_chars, &right_chars, &self.deadline);
         
-        // 构建LCS表
-        let mut lcs_table = vec![vec![0; n + 1]; m + 1];
+        builder::build_items(
+            left,
+            &self.segment_chars(left),
+            right,
+            &self.segment_chars(right),
+            &ops,
+            Pairing::None,
+        )
+    }
+    
+    fn segment_chars(&self, text: &str) -> Vec<Range<usize>> {
+        text.char_indices()
+            .map(|(i, c)| i..i + c.len_utf8())
+            .collect()
+    }
+    
+    /// 按空行分段，段后的空行归入当前段
+    fn segment_paragraphs(&self, text: &str) -> Vec<Range<usize>> {
+        let mut segments = Vec::new();
+        let mut start = 0;
+        let mut offset = 0;
+        let mut has_content = false;
+        let mut after_blank = false;
         
-        for i in 1..=m {
-            for j in 1..=n {
-                if left_chars[i - 1] == right_chars[j - 1] {
-                    lcs_table[i][j] = lcs_table[i - 1][j - 1] + 1;
-                } else {
-                    lcs_table[i][j] = lcs_table[i - 1][j].max(lcs_table[i][j - 1]);
+        for line in text.split_inclusive('\n') {
+            if line.trim().is_empty() {
+                after_blank = has_content;
+            } else {
+                if after_blank {
+                    segments.push(start..offset);
+                    start = offset;
+                    after_blank = false;
                 }
+                has_content = true;
             }
+            offset += line.len();
         }
         
-        // 回溯构建diff
-        let mut items = Vec::new();
-        let mut i = m;
-        let mut j = n;
-        
-        while i > 0 || j > 0 {
-            if i == 0 {
-                // 新增
-                items.push(DiffItem {
-                    id: format!("diff-{}", items.len()),
-                    diff_type: DiffType::Add,
-                    content: right_chars[j - 1].to_string(),
-                    original_content: None,
-                    line_number: Some(j),
-                    position: Position { start: j - 1, end: j },
-                });
-                j -= 1;
-            } else if j == 0 {
-                // 删除
-                items.push(DiffItem {
-                    id: format!("diff-{}", items.len()),
-                    diff_type: DiffType::Remove,
-                    content: left_chars[i - 1].to_string(),
-                    original_content: Some(left_chars[i - 1].to_string()),
-                    line_number: Some(i),
-                    position: Position { start: i - 1, end: i },
-                });
-                i -= 1;
-            } else if left_chars[i - 1] == right_chars[j - 1] {
-                // 相同
-                items.push(DiffItem {
-                    id: format!("diff-{}", items.len()),
-                    diff_type: DiffType::Equal,
-                    content: left_chars[i - 1].to_string(),
-                    original_content: None,
-                    line_number: Some(i),
-                    position: Position { start: i - 1, end: i },
-                });
-                i -= 1;
-                j -= 1;
-            } else if lcs_table[i - 1][j] > lcs_table[i][j - 1] {
-                // 删除
-                items.push(DiffItem {
-                    id: format!("diff-{}", items.len()),
-                    diff_type: DiffType::Remove,
-                    content: left_chars[i - 1].to_string(),
-                    original_content: Some(left_chars[i - 1].to_string()),
-                    line_number: Some(i),
-                    position: Position { start: i - 1, end: i },
-                });
-                i -= 1;
-            } else {
-                // 新增
-                items.push(DiffItem {
-                    id: format!("diff-{}", items.len()),
-                    diff_type: DiffType::Add,
-                    content: right_chars[j - 1].to_string(),
-                    original_content: None,
-                    line_number: Some(j),
-                    position: Position { start: j - 1, end: j },
-                });
-                j -= 1;
-            }
+        if start < text.len() {
+            segments.push(start..text.len());
         }
         
-        items.reverse();
-        items
+        segments
     }
     
-    fn calculate_stats(&self, items: &[DiffItem], left_text: &str, right_text: &str) -> DiffStats {
-        let mut stats = DiffStats {
-            total_changes: 0,
-            additions: 0,
-            deletions: 0,
-            modifications: 0,
-            added_words: 0,
-            deleted_words: 0,
-            similarity: 0.0,
-        };
+    fn segment_chinese_text(&self, text: &str) -> Vec<Range<usize>> {
+        // 中文分句实现，句末标点归入当前句
+        self.split_after(text, |c| "。！？；".contains(c))
+    }
+    
+    fn segment_text(&self, text: &str) -> Vec<Range<usize>> {
+        self.split_after(text, |c| ".!?;".contains(c))
+    }
+    
+    /// 在每个终止符之后切分，返回首尾相接、覆盖全文的字节区间
+    fn split_after(&self, text: &str, is_terminator: impl Fn(char) -> bool) -> Vec<Range<usize>> {
+        let mut segments = Vec::new();
+        let mut start = 0;
         
-        for item in items {
-            match item.diff_type {
-                DiffType::Add => {
-                    stats.additions += 1;
-                    stats.added_words += item.content.split_whitespace().count();
-                }
-                DiffType::Remove => {
-                    stats.deletions += 1;
-                    stats.deleted_words += item.content.split_whitespace().count();
-                }
-                DiffType::Modify => {
-                    stats.modifications += 1;
-                    stats.added_words += item.content.split_whitespace().count();
-                    if let Some(ref original) = item.original_content {
-                        stats.deleted_words += original.split_whitespace().count();
-                    }
-                }
-                DiffType::Equal => {}
+        for (i, c) in text.char_indices() {
+            if is_terminator(c) {
+                let end = i + c.len_utf8();
+                segments.push(start..end);
+                start = end;
             }
         }
         
-        stats.total_changes = stats.additions + stats.deletions + stats.modifications;
-        
-        // 计算相似度
-        let total_chars = left_text.len().max(right_text.len()) as f32;
-        let changed_chars = items.iter()
-            .filter(|item| !matches!(item.diff_type, DiffType::Equal))
-            .map(|item| item.content.len())
-            .sum::<usize>() as f32;
-        
-        stats.similarity = if total_chars > 0.0 {
-            ((total_chars - changed_chars) / total_chars * 100.0).max(0.0).min(100.0)
-        } else {
-            100.0
-        };
+        if start < text.len() {
+            segments.push(start..text.len());
+        }
         
-        stats
+        segments
     }
     
-    fn split_into_chunks(&self, left: &str, right: &str, chunk_size: usize) -> Vec<TextChunk> {
-        let mut chunks = Vec::new();
-        let left_lines: Vec<&str> = left.lines().collect();
-        let right_lines: Vec<&str> = right.lines().collect();
-        
-        let total_lines = left_lines.len().max(right_lines.len());
-        let total_chunks = (total_lines + chunk_size - 1) / chunk_size;
-        
-        for i in 0..total_chunks {
-            let start = i * chunk_size;
-            let end = ((i + 1) * chunk_size).min(total_lines);
-            
-            let left_chunk = left_lines[start.min(left_lines.len())..end.min(left_lines.len())]
-                .join("\n");
-            let right_chunk = right_lines[start.min(right_lines.len())..end.min(right_lines.len())]
-                .join("\n");
-            
-            chunks.push(TextChunk {
-                index: i,
-                total: total_chunks,
-                left: left_chunk,
-                right: right_chunk,
-            });
-        }
+    fn calculate_stats(&self, items: &[DiffItem], left_text: &str, right_text: &str) -> DiffStats {
+        let mut stats = StatsBuilder::default();
+        stats.add_items(items);
+        stats.finish(left_text, right_text)
+    }
+}
+
+/// 把相对于父项计算的子差异换算为全文坐标，并按父项编号重新编号
+fn adopt_inline_changes(children: &mut [DiffItem], parent_id: &str, base: &Position) {
+    for (index, child) in children.iter_mut().enumerate() {
+        child.id = format!("{}-{}", parent_id, index);
+        child.position.left = child.position.left.offset_by(&base.left);
+        child.position.right = child.position.right.offset_by(&base.right);
+        child.line_number = Some(match child.diff_type {
+            DiffType::Add => child.position.right.start_line,
+            _ => child.position.left.start_line,
+        });
         
-        chunks
+        // 更深层的子项与当前子项处于同一局部坐标系
+        adopt_inline_changes(&mut child.inline_changes, &child.id, base);
     }
 }
 
@@ -428,14 +892,18 @@ impl DiffEngine {
 pub struct DiffResult {
     pub items: Vec<DiffItem>,
     pub stats: DiffStats,
+    #[serde(default)]
+    pub metadata: DiffMetadata,
 }
 
-#[derive(Debug, Clone)]
-struct TextChunk {
-    index: usize,
-    total: usize,
-    left: String,
-    right: String,
+/// 结果元数据，便于在同一会话中比较不同算法的输出
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DiffMetadata {
+    pub algorithm: DiffAlgorithm,
+    pub has_chinese: bool,
+    /// 超过对比期限后退回粗粒度对比得到的近似结果
+    #[serde(default)]
+    pub approximate: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -485,6 +953,7 @@ mod tests {
             split_by_paragraph: false,
             split_by_sentence: false,
             use_web_worker: false,
+            ..DiffOptions::default()
         };
         
         let engine = DiffEngine::new(options);
@@ -503,6 +972,7 @@ mod tests {
             split_by_paragraph: false,
             split_by_sentence: true,
             use_web_worker: false,
+            ..DiffOptions::default()
         };
         
         let engine = DiffEngine::new(options);
@@ -513,4 +983,538 @@ mod tests {
         
         assert_eq!(result.stats.modifications, 1);
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_modified_sentence_has_inline_changes() {
+        let engine = DiffEngine::new(DiffOptions {
+            split_by_sentence: true,
+            ..DiffOptions::default()
+        });
+        
+        let left = "第一条。\n甲方应当按月支付服务费用。";
+        let right = "第一条。\n甲方应当按季度支付技术服务费用。";
+        let result = engine.compute_diff(left, right);
+        
+        let modified: Vec<&DiffItem> = result.items.iter()
+            .filter(|item| item.diff_type == DiffType::Modify)
+            .collect();
+        assert_eq!(modified.len(), 1);
+        
+        let changes: Vec<&DiffItem> = modified[0].inline_changes.iter()
+            .filter(|change| change.diff_type != DiffType::Equal)
+            .collect();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].original_content.as_deref(), Some("月"));
+        assert_eq!(changes[0].content, "季度");
+        assert_eq!(changes[1].diff_type, DiffType::Add);
+        assert_eq!(changes[1].content, "技术");
+        
+        // 句内位置换算为全文坐标
+        let range = &changes[1].position.right;
+        assert_eq!(&right[range.byte_start..range.byte_end], "技术");
+        assert_eq!((range.start_line, range.start_column), (2, 10));
+    }
+    
+    #[test]
+    fn test_paragraphs_cascade_to_sentences_and_words() {
+        let engine = DiffEngine::new(DiffOptions {
+            split_by_paragraph: true,
+            ..DiffOptions::default()
+        });
+        
+        let left = "第一章 总则\n\n甲方应当按月支付费用。乙方提供服务。\n\n\n第二章 附则\n";
+        let right = "第一章 总则\n\n甲方应当按季度支付费用。乙方提供服务。\n\n\n第二章 附则\n";
+        
+        assert_eq!(
+            engine.segment_paragraphs(left).iter().map(|r| &left[r.clone()]).collect::<Vec<_>>(),
+            vec!["第一章 总则\n\n", "甲方应当按月支付费用。乙方提供服务。\n\n\n", "第二章 附则\n"]
+        );
+        
+        let result = engine.compute_diff(left, right);
+        let paragraph = result.items.iter()
+            .find(|item| item.diff_type == DiffType::Modify)
+            .unwrap();
+        assert_eq!(paragraph.position.left.start_line, 3);
+        
+        // 段落内按句对比，未改动的句子保持相等
+        let sentence = &paragraph.inline_changes[0];
+        assert_eq!(sentence.diff_type, DiffType::Modify);
+        assert_eq!(sentence.id, format!("{}-0", paragraph.id));
+        assert_eq!(paragraph.inline_changes[1].diff_type, DiffType::Equal);
+        
+        // 句内按词对比，位置为全文坐标
+        let word = sentence.inline_changes.iter()
+            .find(|change| change.diff_type != DiffType::Equal)
+            .unwrap();
+        assert_eq!(word.content, "季度");
+        assert_eq!(word.id, format!("{}-0-1", paragraph.id));
+        let range = &word.position.right;
+        assert_eq!(&right[range.byte_start..range.byte_end], "季度");
+        assert_eq!((range.start_line, range.start_column), (3, 6));
+    }
+    
+    #[test]
+    fn test_moved_sentence_is_reported_once() {
+        let engine = DiffEngine::new(DiffOptions {
+            split_by_sentence: true,
+            ..DiffOptions::default()
+        });
+        
+        let clause = "乙方应当对甲方提供的全部资料承担保密义务。";
+        let left = format!("第一条 总则。\n{}\n第二条 费用。\n第三条 期限。\n", clause);
+        let right = format!("第一条 总则。\n第二条 费用。\n第三条 期限。\n{}\n", clause);
+        let result = engine.compute_diff(&left, &right);
+        
+        let changes: Vec<&DiffItem> = result.items.iter()
+            .filter(|item| item.diff_type != DiffType::Equal)
+            .collect();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].diff_type, DiffType::Move);
+        assert_eq!(changes[0].content.trim(), clause);
+        
+        let (source, target) = (&changes[0].position.left, &changes[0].position.right);
+        assert_eq!(left[source.byte_start..source.byte_end].trim(), clause);
+        assert_eq!(right[target.byte_start..target.byte_end].trim(), clause);
+        assert!(source.end_line < target.end_line);
+        assert_eq!(result.stats.moves, 1);
+        assert_eq!(result.stats.deletions + result.stats.additions, 0);
+        
+        // 移动后略有改动的句子附带词级差异，来源位置为左侧坐标
+        let edited = right.replace("全部资料", "资料");
+        let result = engine.compute_diff(&left, &edited);
+        let moved = result.items.iter().find(|item| item.diff_type == DiffType::Move).unwrap();
+        let removed = moved.inline_changes.iter()
+            .find(|change| change.diff_type == DiffType::Remove)
+            .unwrap();
+        assert_eq!(removed.content, "全部");
+        assert_eq!(&left[removed.position.left.byte_start..removed.position.left.byte_end], "全部");
+        
+        // 阈值大于 1 时关闭移动检测
+        let engine = DiffEngine::new(DiffOptions {
+            split_by_sentence: true,
+            move_threshold: Some(1.1),
+            ..DiffOptions::default()
+        });
+        assert_eq!(engine.compute_diff(&left, &right).stats.moves, 0);
+    }
+    
+    #[test]
+    fn test_ignore_options_keep_original_text() {
+        let engine = DiffEngine::new(DiffOptions {
+            ignore_case: true,
+            ignore_whitespace: true,
+            split_by_sentence: true,
+            ..DiffOptions::default()
+        });
+        
+        let left = "Hello World.\nThe Fee is Due.";
+        let right = "hello   world.\n\nThe FEE is due now.";
+        let result = engine.compute_diff(left, right);
+        
+        assert_eq!(result.items.len(), 2);
+        let equal = &result.items[0];
+        assert_eq!(equal.diff_type, DiffType::Equal);
+        assert_eq!(equal.content, "hello   world.");
+        assert_eq!(equal.original_content.as_deref(), Some("Hello World."));
+        
+        // 修改项保留原文大小写与换行，偏移指向原文
+        let modified = &result.items[1];
+        assert_eq!(modified.diff_type, DiffType::Modify);
+        assert_eq!(modified.original_content.as_deref(), Some("\nThe Fee is Due."));
+        assert_eq!(modified.content, "\n\nThe FEE is due now.");
+        assert_eq!(modified.position.right.byte_start, 14);
+        
+        let changes: Vec<&DiffItem> = modified.inline_changes.iter()
+            .filter(|change| change.diff_type != DiffType::Equal)
+            .collect();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].content.trim(), "now");
+    }
+    
+    #[test]
+    fn test_moves_are_detected_in_character_and_word_modes() {
+        let clause = "乙方应当对甲方提供的全部资料承担保密义务。";
+        let middle = "第四条 交付。\n第五条 费用按季度结算。\n第六条 验收合格后付款。\n第七条 违约。";
+        let left = format!("第三条 保密。{}\n{}\n", clause, middle);
+        let right = format!("第三条 保密。\n{}{}\n", middle, clause);
+        
+        // 默认选项按字符对比，剪切粘贴的条款仍识别为一处移动
+        for options in [DiffOptions::default(), DiffOptions { split_by_word: true, ..DiffOptions::default() }] {
+            let result = DiffEngine::new(options).compute_diff(&left, &right);
+            let changes: Vec<&DiffItem> = result.items.iter()
+                .filter(|item| item.diff_type != DiffType::Equal)
+                .collect();
+            assert_eq!(changes.len(), 1);
+            assert_eq!(changes[0].diff_type, DiffType::Move);
+            // 字符对比中句号可能落在条款的任一端
+            assert_eq!(changes[0].content.trim_matches('。'), clause.trim_end_matches('。'));
+            assert_eq!(result.stats.moves, 1);
+        }
+    }
+    
+    #[test]
+    fn test_diff3_classifies_and_merges() {
+        let engine = DiffEngine::new(DiffOptions::default());
+        
+        // 相邻的两侧修改会归入同一冲突块，因此各处修改之间隔开一行
+        let base = "一、标的\n二、数量\n三、价款\n四、交付\n五、违约\n六、期限\n七、争议\n";
+        let left = "一、标的物\n二、数量\n三、价款\n四、交付\n五、违约责任\n六、期限\n七、争议解决\n";
+        let right = "一、标的\n二、数量\n三、价款及支付\n四、交付\n五、违约金\n六、期限\n七、争议解决\n";
+        
+        let result = engine.compute_diff3(base, left, right);
+        let kinds: Vec<Diff3ChunkType> = result.chunks.iter()
+            .filter(|chunk| chunk.chunk_type != Diff3ChunkType::Unchanged)
+            .map(|chunk| chunk.chunk_type)
+            .collect();
+        assert_eq!(kinds, vec![
+            Diff3ChunkType::LeftOnly,
+            Diff3ChunkType::RightOnly,
+            Diff3ChunkType::Conflict,
+            Diff3ChunkType::BothSame,
+        ]);
+        let conflict = result.chunks.iter()
+            .find(|chunk| chunk.chunk_type == Diff3ChunkType::Conflict)
+            .unwrap();
+        assert_eq!(conflict.right_range.start_line, 5);
+        
+        let merged = engine.merge3(base, left, right);
+        assert!(merged.has_conflicts);
+        assert_eq!(merged.stats.conflicts, 1);
+        assert_eq!(
+            merged.merged_text,
+            "一、标的物\n二、数量\n三、价款及支付\n四、交付\n\
+             <<<<<<< 左侧\n五、违约责任\n||||||| 基线\n五、违约\n=======\n五、违约金\n>>>>>>> 右侧\n\
+             六、期限\n七、争议解决\n"
+        );
+    }
+    
+    #[test]
+    fn test_diff3_same_change_modulo_ignore_rules_is_not_a_conflict() {
+        let engine = DiffEngine::new(DiffOptions {
+            ignore_patterns: vec![r"\d{4}-\d{2}-\d{2}".to_string()],
+            ..DiffOptions::default()
+        });
+        
+        // 两侧的修改只有被忽略的日期不同
+        let base = "一、标的\n二、价款 100 元\n三、期限\n";
+        let left = "一、标的\n二、价款 200 元（2024-01-01 确认）\n三、期限\n";
+        let right = "一、标的\n二、价款 200 元（2024-03-15 确认）\n三、期限\n";
+        
+        let result = engine.compute_diff3(base, left, right);
+        assert_eq!(result.stats.conflicts, 0);
+        assert_eq!(result.stats.both_same, 1);
+    }
+    
+    #[test]
+    fn test_sentence_inserted_at_top() {
+        let options = DiffOptions {
+            ignore_case: false,
+            ignore_whitespace: false,
+            ignore_punctuation: false,
+            split_by_paragraph: false,
+            split_by_sentence: true,
+            use_web_worker: false,
+            ..DiffOptions::default()
+        };
+        
+        let engine = DiffEngine::new(options);
+        let result = engine.compute_diff(
+            "第一条。第二条。第三条。",
+            "新增条款。第一条。第二条。第三条。"
+        );
+        
+        assert_eq!(result.stats.additions, 1);
+        assert_eq!(result.stats.modifications, 0);
+        assert_eq!(result.stats.deletions, 0);
+    }
+    
+    #[test]
+    fn test_every_algorithm_reconstructs_both_sides() {
+        let left = "fn main() {\n    let a = 1;\n}\n\nfn helper() {\n    let b = 2;\n}\n";
+        let right = "fn main() {\n    let a = 1;\n    let c = 3;\n}\n\nfn helper() {\n}\n";
+        
+        for algorithm in [
+            DiffAlgorithm::Myers,
+            DiffAlgorithm::Patience,
+            DiffAlgorithm::Histogram,
+            DiffAlgorithm::LCS,
+        ] {
+            let engine = DiffEngine::new(DiffOptions {
+                split_by_sentence: true,
+                algorithm: Some(algorithm),
+                // 关闭移动检测，按顺序拼接即可还原两侧文本
+                move_threshold: Some(1.1),
+                ..DiffOptions::default()
+            });
+            let result = engine.compute_diff(left, right);
+            
+            let mut rebuilt_left = String::new();
+            let mut rebuilt_right = String::new();
+            for item in &result.items {
+                match item.diff_type {
+                    DiffType::Equal => {
+                        rebuilt_left.push_str(&item.content);
+                        rebuilt_right.push_str(&item.content);
+                    }
+                    DiffType::Add => rebuilt_right.push_str(&item.content),
+                    DiffType::Remove => rebuilt_left.push_str(&item.content),
+                    DiffType::Modify => {
+                        rebuilt_left.push_str(item.original_content.as_deref().unwrap_or_default());
+                        rebuilt_right.push_str(&item.content);
+                    }
+                    DiffType::Move => unreachable!(),
+                }
+            }
+            
+            assert_eq!(rebuilt_left, left, "{:?}", algorithm);
+            assert_eq!(rebuilt_right, right, "{:?}", algorithm);
+            assert_eq!(result.metadata.algorithm, algorithm);
+        }
+    }
+    
+    #[test]
+    fn test_word_diff_reports_changed_words() {
+        let engine = DiffEngine::new(DiffOptions {
+            split_by_word: true,
+            ..DiffOptions::default()
+        });
+        
+        let result = engine.compute_diff(
+            "The quick brown fox. 甲方应当支付违约金。",
+            "The slow brown fox. 甲方应当支付赔偿金。"
+        );
+        
+        let changes: Vec<(&str, &str)> = result.items.iter()
+            .filter(|item| matches!(item.diff_type, DiffType::Modify))
+            .map(|item| (item.original_content.as_deref().unwrap_or_default(), item.content.as_str()))
+            .collect();
+        
+        assert_eq!(changes, vec![("quick", "slow"), ("违约金", "赔偿金")]);
+        assert_eq!(result.stats.total_changes, 2);
+    }
+    
+    #[test]
+    fn test_large_char_diff_uses_linear_memory() {
+        let options = DiffOptions {
+            ignore_case: false,
+            ignore_whitespace: false,
+            ignore_punctuation: false,
+            split_by_paragraph: false,
+            split_by_sentence: false,
+            use_web_worker: false,
+            ..DiffOptions::default()
+        };
+        
+        // 20万字符的 LCS 表需要约 160GB 内存，线性空间算法应能直接完成
+        let left: String = (0..20_000).map(|i| format!("line{:05}\n", i)).collect();
+        let right = left.replacen("line10000", "LINE10000", 1);
+        
+        let engine = DiffEngine::new(options);
+        let result = engine.compute_diff(&left, &right);
+        
+        assert_eq!(result.stats.additions, 1);
+        assert_eq!(result.stats.deletions, 1);
+    }
+    
+    #[test]
+    fn test_char_changes_are_coalesced_with_offsets() {
+        let options = DiffOptions {
+            ignore_case: false,
+            ignore_whitespace: false,
+            ignore_punctuation: false,
+            split_by_paragraph: false,
+            split_by_sentence: false,
+            use_web_worker: false,
+            ..DiffOptions::default()
+        };
+        
+        let engine = DiffEngine::new(options);
+        let result = engine.compute_diff("第一行\n甲方付款", "第一行\n甲方按时付款");
+        
+        let added: Vec<&DiffItem> = result.items.iter()
+            .filter(|item| matches!(item.diff_type, DiffType::Add))
+            .collect();
+        
+        assert_eq!(added.len(), 1);
+        assert_eq!(added[0].content, "按时");
+        assert_eq!(added[0].line_number, Some(2));
+        assert_eq!(added[0].position.right, TextRange {
+            byte_start: 16,
+            byte_end: 22,
+            char_start: 6,
+            char_end: 8,
+            start_line: 2,
+            start_column: 3,
+            end_line: 2,
+            end_column: 5,
+        });
+        assert_eq!(added[0].position.left.byte_start, 16);
+        assert_eq!(added[0].position.left.byte_end, 16);
+    }
+    
+    #[test]
+    fn test_timeout_falls_back_to_line_level() {
+        // 两侧毫无公共结构的伪随机汉字，字符级 Myers 接近平方复杂度
+        let random_text = |mut seed: u32| -> String {
+            (0..20_000)
+                .map(|i| {
+                    seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                    if i % 40 == 39 {
+                        '\n'
+                    } else {
+                        char::from_u32(0x4e00 + (seed >> 16) % 2000).unwrap()
+                    }
+                })
+                .collect()
+        };
+        let left = random_text(1);
+        let right = random_text(2);
+        
+        let engine = DiffEngine::new(DiffOptions {
+            timeout_ms: Some(1),
+            ..DiffOptions::default()
+        });
+        let result = engine.compute_diff(&left, &right);
+        assert!(result.metadata.approximate);
+        assert!(result.items.iter().all(|item| item.position.left.start_column == 1 && item.position.right.start_column == 1));
+        
+        let engine = DiffEngine::new(DiffOptions {
+            timeout_ms: Some(10_000),
+            ..DiffOptions::default()
+        });
+        assert!(!engine.compute_diff("甲方付款", "乙方付款").metadata.approximate);
+    }
+    
+    #[test]
+    fn test_cancel_interrupts_whole_document_diff() {
+        // 不足分区阈值的文本整体对比，取消由算法内部的期限检查响应
+        let random_text = |mut seed: u32| -> String {
+            (0..20_000)
+                .map(|_| {
+                    seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                    char::from_u32(0x4e00 + (seed >> 16) % 2000).unwrap()
+                })
+                .collect()
+        };
+        let left = random_text(1);
+        let right = random_text(2);
+        
+        let token = CancellationToken::new();
+        let trigger = token.clone();
+        let engine = DiffEngine::new(DiffOptions::default()).with_cancellation(token);
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            trigger.cancel();
+        });
+        
+        let start = std::time::Instant::now();
+        assert_eq!(engine.try_compute_diff(&left, &right).unwrap_err(), DiffError::Cancelled);
+        assert!(start.elapsed() < Duration::from_secs(5));
+        canceller.join().unwrap();
+    }
+    
+    #[test]
+    fn test_noise_rules_ignore_numbering_dates_and_reflow() {
+        let left = "1. 本合同自签订之日起生效，有效期一年。\n2. 更新日期：2024-01-05\n";
+        let right = "3. 本合同自签订之日起生效，\n有效期一年。\n4. 更新日期：2025-03-18\n";
+        
+        let engine = DiffEngine::new(DiffOptions {
+            split_by_sentence: true,
+            ignore_patterns: vec![r"\d{4}-\d{2}-\d{2}".to_string()],
+            ignore_reflow: true,
+            ignore_list_numbering: true,
+            ..DiffOptions::default()
+        });
+        let result = engine.try_compute_diff(left, right).unwrap();
+        assert_eq!(result.stats.total_changes, 0);
+        
+        // 显示的仍是原文
+        let rebuilt: String = result.items.iter().map(|item| item.content.as_str()).collect();
+        assert_eq!(rebuilt, right);
+        
+        let engine = DiffEngine::new(DiffOptions {
+            ignore_patterns: vec!["(".to_string()],
+            ..DiffOptions::default()
+        });
+        assert!(matches!(engine.try_compute_diff(left, right), Err(DiffError::InvalidPattern(_))));
+    }
+    
+    #[test]
+    fn test_equivalence_rules_canonicalize_before_comparison() {
+        let profile = RuleProfile::from_json(r#"{
+            "name": "合同条款",
+            "equivalence_rules": [
+                {"kind": "terms", "terms": ["Limited", "Ltd."]},
+                {"kind": "regex", "pattern": "¥\\s*(\\d+)|(\\d+)\\s*元", "replacement": "¥$1$2"},
+                {"kind": "chinese_numerals"}
+            ]
+        }"#).unwrap();
+        let engine = DiffEngine::new(profile.apply_to(DiffOptions::default()));
+        
+        let left = "第十二条 Acme Ltd. 应支付 500 元。";
+        let right = "第12条 Acme Limited 应支付 ¥500。";
+        let result = engine.try_compute_diff(left, right).unwrap();
+        assert_eq!(result.stats.total_changes, 0);
+        let rebuilt: String = result.items.iter().map(|item| item.content.as_str()).collect();
+        assert_eq!(rebuilt, right);
+        
+        // 数值不同的条款号仍是改动
+        let result = engine.compute_diff("第十二条", "第13条");
+        assert!(result.stats.total_changes > 0);
+    }
+    
+    #[test]
+    fn test_line_mode_reports_line_ranges_for_both_sides() {
+        let left = "host = a\nport = 80\nmode = x\n";
+        let right = "# 配置\nhost = a\nport = 8080\nmode = x";
+        
+        let engine = DiffEngine::new(DiffOptions {
+            split_by_line: true,
+            ..DiffOptions::default()
+        });
+        let result = engine.compute_diff(left, right);
+        let summary: Vec<_> = result
+            .items
+            .iter()
+            .map(|item| (item.diff_type, item.left_line.unwrap(), item.right_line.unwrap()))
+            .collect();
+        let lines = |start, end| LineRange { start, end };
+        assert_eq!(summary, vec![
+            (DiffType::Add, lines(1, 1), lines(1, 2)),
+            (DiffType::Equal, lines(1, 2), lines(2, 3)),
+            (DiffType::Modify, lines(2, 3), lines(3, 4)),
+            // 末行失去了换行符
+            (DiffType::Modify, lines(3, 4), lines(4, 5)),
+        ]);
+        
+        // 修改逐行配对，行内给出词级差异
+        let modified = &result.items[2];
+        assert!(modified.inline_changes.iter().any(|change| change.diff_type == DiffType::Modify && change.content == "8080"));
+    }
+    
+    #[test]
+    fn test_line_mode_detects_moved_lines() {
+        let left = "timeout = 30 seconds for upstream
+host = a
+port = 80
+";
+        let right = "host = a
+port = 80
+timeout = 30 seconds for upstream
+";
+        
+        let engine = DiffEngine::new(DiffOptions {
+            split_by_line: true,
+            ..DiffOptions::default()
+        });
+        let result = engine.compute_diff(left, right);
+        let changes: Vec<&DiffItem> = result.items.iter()
+            .filter(|item| item.diff_type != DiffType::Equal)
+            .collect();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].diff_type, DiffType::Move);
+        assert_eq!(changes[0].left_line, Some(LineRange { start: 1, end: 2 }));
+        assert_eq!(changes[0].right_line, Some(LineRange { start: 3, end: 4 }));
+        assert_eq!(result.stats.moves, 1);
+    }
+}
@@ -0,0 +1,198 @@
+// 移动块检测：把内容相近的删除块与新增块配对为移动
+use std::collections::{HashMap, HashSet};
+
+use super::tokenizer::Tokenizer;
+use super::{DiffItem, DiffType};
+
+/// 默认的移动判定相似度阈值
+pub const DEFAULT_THRESHOLD: f32 = 0.8;
+
+/// 参与检测的最少非空白字符数，避免把零散的短句误判为移动
+const MIN_BLOCK_CHARS: usize = 8;
+
+/// 出现在超过该数量新增块中的词元视为常用词，不用于筛选候选
+const MAX_POSTINGS: usize = 64;
+
+/// 每个删除块最多与共有词元最多的这些新增块计算相似度
+const MAX_CANDIDATES: usize = 16;
+
+/// 词袋：词元 → 出现次数，以及词元总数
+struct Bag<'a> {
+    counts: HashMap<&'a str, usize>,
+    len: usize,
+}
+
+impl<'a> Bag<'a> {
+    fn new(text: &'a str) -> Self {
+        let mut counts = HashMap::new();
+        let mut len = 0;
+
+        for range in Tokenizer::builtin().tokenize(text) {
+            let token = &text[range];
+            if token.trim().is_empty() {
+                continue;
+            }
+            *counts.entry(token).or_insert(0) += 1;
+            len += 1;
+        }
+
+        Self { counts, len }
+    }
+
+    /// Dice 系数：2 × 共有词元数 / 两侧词元总数
+    fn similarity(&self, other: &Bag) -> f32 {
+        if self.len + other.len == 0 {
+            return 0.0;
+        }
+
+        let (small, large) = if self.counts.len() <= other.counts.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let common: usize = small
+            .counts
+            .iter()
+            .map(|(token, &count)| count.min(large.counts.get(token).copied().unwrap_or(0)))
+            .sum();
+
+        2.0 * common as f32 / (self.len + other.len) as f32
+    }
+
+    /// 相似度的上界，只取决于两侧词元数
+    fn max_similarity(&self, other: &Bag) -> f32 {
+        2.0 * self.len.min(other.len) as f32 / (self.len + other.len).max(1) as f32
+    }
+}
+
+/// 检测移动块
+///
+/// 相似度不低于 `threshold` 的删除项与新增项按相似度从高到低贪心配对：
+/// 新增项改为 `Move`，`position.left` 为来源位置、`position.right` 为目标位置，
+/// `original_content` 为来源内容；配对成功的删除项从结果中移除。
+/// 返回被改为移动的差异项在结果中的下标。
+///
+/// 候选按词元倒排索引筛选：只与共有非常用词元的新增块比较，每个删除块最多比较
+/// `MAX_CANDIDATES` 个，避免删除块与新增块两两计算相似度。
+pub fn detect_moves(items: &mut Vec<DiffItem>, threshold: f32) -> Vec<usize> {
+    let candidates = |diff_type: DiffType| -> Vec<(usize, Bag)> {
+        items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.diff_type == diff_type)
+            .filter(|(_, item)| item.content.chars().filter(|c| !c.is_whitespace()).count() >= MIN_BLOCK_CHARS)
+            .map(|(index, item)| (index, Bag::new(&item.content)))
+            .collect()
+    };
+    let removed = candidates(DiffType::Remove);
+    let added = candidates(DiffType::Add);
+
+    // 词元 → 含有该词元的新增块在 added 中的下标
+    let mut postings: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (position, (_, add_bag)) in added.iter().enumerate() {
+        for &token in add_bag.counts.keys() {
+            postings.entry(token).or_default().push(position);
+        }
+    }
+
+    let mut scored = Vec::new();
+    for (remove_index, remove_bag) in &removed {
+        let mut shared: HashMap<usize, usize> = HashMap::new();
+        for token in remove_bag.counts.keys() {
+            match postings.get(token) {
+                Some(positions) if positions.len() <= MAX_POSTINGS => {
+                    for &position in positions {
+                        *shared.entry(position).or_insert(0) += 1;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut candidates: Vec<(usize, usize)> = shared.into_iter().collect();
+        candidates.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        candidates.truncate(MAX_CANDIDATES);
+
+        for (position, _) in candidates {
+            let (add_index, add_bag) = &added[position];
+            if remove_bag.max_similarity(add_bag) < threshold {
+                continue;
+            }
+            let score = remove_bag.similarity(add_bag);
+            if score >= threshold {
+                scored.push((score, *remove_index, *add_index));
+            }
+        }
+    }
+    // 相似度相同时优先配对靠前的块，保证结果稳定
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0).then((a.1, a.2).cmp(&(b.1, b.2))));
+
+    let mut used_removed = HashSet::new();
+    let mut used_added = HashSet::new();
+    for (_, remove_index, add_index) in scored {
+        if used_removed.contains(&remove_index) || used_added.contains(&add_index) {
+            continue;
+        }
+        used_removed.insert(remove_index);
+        used_added.insert(add_index);
+
+        let source = items[remove_index].clone();
+        let target = &mut items[add_index];
+        target.diff_type = DiffType::Move;
+        target.original_content = Some(source.content);
+        target.position.left = source.position.left;
+    }
+
+    if used_removed.is_empty() {
+        return Vec::new();
+    }
+
+    let mut index = 0;
+    items.retain(|_| {
+        index += 1;
+        !used_removed.contains(&(index - 1))
+    });
+
+    items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| item.diff_type == DiffType::Move)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff_engine::Position;
+
+    fn block(diff_type: DiffType, content: String) -> DiffItem {
+        DiffItem {
+            id: String::new(),
+            diff_type,
+            content,
+            original_content: None,
+            line_number: None,
+            position: Position::default(),
+            inline_changes: Vec::new(),
+            left_line: None,
+            right_line: None,
+            path: None,
+        }
+    }
+
+    #[test]
+    fn test_many_blocks_are_paired_through_shared_tokens() {
+        // 删除块与新增块各数千个，逐一比较会是千万次相似度计算
+        let sentence = |i: usize| format!("clause {} covers item{} and term{} with note{}", i % 7, i, i * 3, i * 5);
+        let count = 3000;
+        let mut items: Vec<DiffItem> = (0..count).map(|i| block(DiffType::Remove, sentence(i))).collect();
+        items.extend((0..count).rev().map(|i| block(DiffType::Add, sentence(i))));
+
+        let moved = detect_moves(&mut items, DEFAULT_THRESHOLD);
+
+        assert_eq!(moved.len(), count);
+        assert_eq!(items.len(), count);
+        assert!(items.iter().all(|item| item.original_content.as_deref() == Some(item.content.as_str())));
+    }
+}
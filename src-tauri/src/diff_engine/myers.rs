@@ -0,0 +1,254 @@
+// Myers O(ND) 差分算法（线性空间版本）
+//
+// 采用 Myers 论文中的“中间蛇”分治法：每次只保留前向与后向两条 V 数组，
+// 内存占用为 O(N + M)，不再需要 O(N·M) 的 LCS 表。
+use std::ops::{Index, IndexMut, Range};
+
+use super::control::Deadline;
+use super::ops::{DiffOp, OpsBuilder};
+
+/// 对两个序列执行 Myers Diff
+#[cfg(test)]
+pub fn diff<T: Eq>(old: &[T], new: &[T]) -> Vec<DiffOp> {
+    diff_before(old, new, &Deadline::never())
+}
+
+/// 在期限前执行 Myers Diff，超过期限后尚未求解的区间整体视为删除加插入
+pub fn diff_before<T: Eq>(old: &[T], new: &[T], deadline: &Deadline) -> Vec<DiffOp> {
+    let mut builder = OpsBuilder::new();
+    diff_range(old, 0..old.len(), new, 0..new.len(), deadline, &mut builder);
+    builder.finish()
+}
+
+/// 对两个序列的子区间执行 Myers Diff，结果追加到 `builder`
+///
+/// 供其他算法在找不到锚点时回退使用。
+pub fn diff_range<T: Eq>(
+    old: &[T],
+    old_range: Range<usize>,
+    new: &[T],
+    new_range: Range<usize>,
+    deadline: &Deadline,
+    builder: &mut OpsBuilder,
+) {
+    let max_d = max_d(old_range.len(), new_range.len());
+    let mut vf = V::new(max_d);
+    let mut vb = V::new(max_d);
+
+    conquer(old, old_range, new, new_range, &mut vf, &mut vb, deadline, builder);
+}
+
+fn max_d(len1: usize, len2: usize) -> usize {
+    (len1 + len2).div_ceil(2) + 1
+}
+
+/// 以对角线编号 k（可为负）为下标的 V 数组
+struct V {
+    offset: isize,
+    v: Vec<usize>,
+}
+
+impl V {
+    fn new(max_d: usize) -> Self {
+        Self {
+            offset: max_d as isize,
+            v: vec![0; 2 * max_d],
+        }
+    }
+}
+
+impl Index<isize> for V {
+    type Output = usize;
+
+    fn index(&self, index: isize) -> &usize {
+        &self.v[(index + self.offset) as usize]
+    }
+}
+
+impl IndexMut<isize> for V {
+    fn index_mut(&mut self, index: isize) -> &mut usize {
+        &mut self.v[(index + self.offset) as usize]
+    }
+}
+
+fn common_prefix_len<T: Eq>(old: &[T], old_range: Range<usize>, new: &[T], new_range: Range<usize>) -> usize {
+    old[old_range]
+        .iter()
+        .zip(new[new_range].iter())
+        .take_while(|(a, b)| a == b)
+        .count()
+}
+
+fn common_suffix_len<T: Eq>(old: &[T], old_range: Range<usize>, new: &[T], new_range: Range<usize>) -> usize {
+    old[old_range]
+        .iter()
+        .rev()
+        .zip(new[new_range].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count()
+}
+
+/// 寻找中间蛇，返回分割点（左侧位置, 右侧位置）；超过期限时返回 `None`
+fn find_middle_snake<T: Eq>(
+    old: &[T],
+    old_range: Range<usize>,
+    new: &[T],
+    new_range: Range<usize>,
+    vf: &mut V,
+    vb: &mut V,
+    deadline: &Deadline,
+) -> Option<(usize, usize)> {
+    let n = old_range.len();
+    let m = new_range.len();
+
+    // delta 为奇数时前向搜索负责检测重叠，偶数时由后向搜索负责
+    let delta = n as isize - m as isize;
+    let odd = delta & 1 == 1;
+
+    vf[1] = 0;
+    vb[1] = 0;
+
+    let d_max = max_d(n, m) as isize;
+
+    for d in 0..d_max {
+        if deadline.expired() {
+            return None;
+        }
+
+        // 前向搜索
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = if k == -d || (k != d && vf[k - 1] < vf[k + 1]) {
+                vf[k + 1]
+            } else {
+                vf[k - 1] + 1
+            };
+            let y = (x as isize - k) as usize;
+
+            let (x0, y0) = (x, y);
+            if x < n && y < m {
+                x += common_prefix_len(
+                    old,
+                    old_range.start + x..old_range.end,
+                    new,
+                    new_range.start + y..new_range.end,
+                );
+            }
+
+            vf[k] = x;
+
+            if odd && (k - delta).abs() < d && vf[k] + vb[-(k - delta)] >= n {
+                return Some((x0 + old_range.start, y0 + new_range.start));
+            }
+        }
+
+        // 后向搜索
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = if k == -d || (k != d && vb[k - 1] < vb[k + 1]) {
+                vb[k + 1]
+            } else {
+                vb[k - 1] + 1
+            };
+            let mut y = (x as isize - k) as usize;
+
+            if x < n && y < m {
+                let advance = common_suffix_len(
+                    old,
+                    old_range.start..old_range.start + n - x,
+                    new,
+                    new_range.start..new_range.start + m - y,
+                );
+                x += advance;
+                y += advance;
+            }
+
+            vb[k] = x;
+
+            if !odd && (k - delta).abs() <= d && vb[k] + vf[-(k - delta)] >= n {
+                return Some((n - x + old_range.start, m - y + new_range.start));
+            }
+        }
+    }
+
+    None
+}
+
+#[allow(clippy::too_many_arguments)]
+fn conquer<T: Eq>(
+    old: &[T],
+    mut old_range: Range<usize>,
+    new: &[T],
+    mut new_range: Range<usize>,
+    vf: &mut V,
+    vb: &mut V,
+    deadline: &Deadline,
+    builder: &mut OpsBuilder,
+) {
+    // 去掉公共前缀
+    let prefix = common_prefix_len(old, old_range.clone(), new, new_range.clone());
+    builder.equal(old_range.start, new_range.start, prefix);
+    old_range.start += prefix;
+    new_range.start += prefix;
+
+    // 去掉公共后缀（最后再输出）
+    let suffix = common_suffix_len(old, old_range.clone(), new, new_range.clone());
+    old_range.end -= suffix;
+    new_range.end -= suffix;
+
+    if old_range.is_empty() && new_range.is_empty() {
+        // 无剩余内容
+    } else if new_range.is_empty() {
+        builder.delete(old_range.start, old_range.len(), new_range.start);
+    } else if old_range.is_empty() {
+        builder.insert(old_range.start, new_range.start, new_range.len());
+    } else if let Some((x, y)) =
+        find_middle_snake(old, old_range.clone(), new, new_range.clone(), vf, vb, deadline)
+    {
+        conquer(old, old_range.start..x, new, new_range.start..y, vf, vb, deadline, builder);
+        conquer(old, x..old_range.end, new, y..new_range.end, vf, vb, deadline, builder);
+    } else {
+        builder.delete(old_range.start, old_range.len(), new_range.start);
+        builder.insert(old_range.end, new_range.start, new_range.len());
+    }
+
+    builder.equal(old_range.end, new_range.end, suffix);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 按编辑操作把左侧序列改写为右侧序列，用于校验结果
+    fn apply<T: Clone>(old: &[T], new: &[T], ops: &[DiffOp]) -> Vec<T> {
+        let mut result = Vec::new();
+        for op in ops {
+            match *op {
+                DiffOp::Equal { old_index, len, .. } => {
+                    result.extend_from_slice(&old[old_index..old_index + len]);
+                }
+                DiffOp::Insert { new_index, new_len, .. } => {
+                    result.extend_from_slice(&new[new_index..new_index + new_len]);
+                }
+                DiffOp::Delete { .. } => {}
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_myers_is_minimal() {
+        let old: Vec<char> = "ABCABBA".chars().collect();
+        let new: Vec<char> = "CBABAC".chars().collect();
+
+        let ops = diff(&old, &new);
+
+        let equal: usize = ops
+            .iter()
+            .map(|op| match op {
+                DiffOp::Equal { len, .. } => *len,
+                _ => 0,
+            })
+            .sum();
+        assert_eq!(equal, 4);
+        assert_eq!(apply(&old, &new, &ops), new);
+    }
+}
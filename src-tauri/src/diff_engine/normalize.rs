@@ -0,0 +1,377 @@
+// 比较键归一化：忽略大小写、空白、标点以及 Unicode 规范化、全半角、繁简折叠，
+// 和按正则、列表编号、软换行、代码注释屏蔽的文本都只影响比较，结果仍保留原文与原始偏移
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use regex::Regex;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use super::code::{self, CodeLanguage};
+use super::rules::CompiledRules;
+use super::tokenizer::{is_chinese_char, is_cjk_ideograph};
+use super::{DiffOptions, UnicodeForm};
+
+/// 内置繁体 → 简体折叠表
+const TRADITIONAL_TO_SIMPLIFIED: &str = include_str!("dict/t2s.txt");
+
+fn traditional_to_simplified() -> &'static HashMap<char, char> {
+    static TABLE: OnceLock<HashMap<char, char>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        TRADITIONAL_TO_SIMPLIFIED
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .flat_map(str::split_whitespace)
+            .filter_map(|pair| {
+                let mut chars = pair.chars();
+                Some((chars.next()?, chars.next()?))
+            })
+            .collect()
+    })
+}
+
+/// 全角 ASCII 字符与全角空格折叠为半角
+fn fold_width(c: char) -> char {
+    match c {
+        '\u{ff01}'..='\u{ff5e}' => char::from_u32(c as u32 - 0xfee0).unwrap_or(c),
+        '\u{3000}' => ' ',
+        _ => c,
+    }
+}
+
+/// 行首的列表编号：1.、1.2)、a.、(1)、（一）、一、等，不含缩进
+fn list_marker() -> &'static Regex {
+    static MARKER: OnceLock<Regex> = OnceLock::new();
+    MARKER.get_or_init(|| {
+        Regex::new(
+            r"(?m)^[ \t]*((?:\d+(?:\.\d+)*[.)．]|[a-zA-Z][.)])[ \t]+|(?:\d+|[一二三四五六七八九十百千]+)、[ \t]*|[(（](?:\d+|[a-zA-Z]|[一二三四五六七八九十百千]+)[)）][ \t]*)",
+        )
+        .expect("列表编号正则无效")
+    })
+}
+
+/// 行首的无序列表符号
+fn is_bullet(line: &str) -> bool {
+    let mut chars = line.trim_start_matches([' ', '\t']).chars();
+    matches!(chars.next(), Some('-' | '*' | '+' | '•' | '·')) && chars.next().is_some_and(|c| c == ' ' || c == '\t')
+}
+
+/// 比较时替换的原文区间及其替代文本（忽略的文本替代为空）
+type Mask = (Range<usize>, Cow<'static, str>);
+
+/// 比较键归一化器
+#[derive(Debug, Clone, Copy)]
+pub struct Normalizer<'a> {
+    ignore_case: bool,
+    ignore_whitespace: bool,
+    ignore_punctuation: bool,
+    unicode_form: Option<UnicodeForm>,
+    fold_width: bool,
+    fold_chinese_variants: bool,
+    ignore_reflow: bool,
+    ignore_list_numbering: bool,
+    /// 忽略注释时注释所属的语言
+    ignore_comments: Option<CodeLanguage>,
+    rules: &'a CompiledRules,
+}
+
+impl<'a> Normalizer<'a> {
+    /// `rules` 为由同一选项编译的规则
+    pub fn new(options: &DiffOptions, rules: &'a CompiledRules) -> Self {
+        Self {
+            ignore_case: options.ignore_case,
+            ignore_whitespace: options.ignore_whitespace,
+            ignore_punctuation: options.ignore_punctuation,
+            unicode_form: options.unicode_form,
+            fold_width: options.fold_width,
+            fold_chinese_variants: options.fold_chinese_variants,
+            ignore_reflow: options.ignore_reflow,
+            ignore_list_numbering: options.ignore_list_numbering,
+            ignore_comments: options.code_language.filter(|_| options.ignore_comments),
+            rules,
+        }
+    }
+
+    /// 是否不做任何归一化
+    pub fn is_identity(&self) -> bool {
+        !(self.folds_chars() || self.masks_text())
+    }
+
+    /// 是否逐字符折叠或忽略
+    fn folds_chars(&self) -> bool {
+        self.ignore_case
+            || self.ignore_whitespace
+            || self.ignore_punctuation
+            || self.unicode_form.is_some()
+            || self.fold_width
+            || self.fold_chinese_variants
+    }
+
+    /// 是否需要结合上下文屏蔽部分原文
+    fn masks_text(&self) -> bool {
+        self.ignore_reflow || self.ignore_list_numbering || self.ignore_comments.is_some() || !self.rules.is_empty()
+    }
+
+    /// 计算比较单元的比较键
+    ///
+    /// 依次做 Unicode 规范化、全半角折叠、繁简折叠，再按忽略选项处理；
+    /// 忽略空白时连续空白折叠为一个空格并去掉首尾空白。
+    /// 规则、列表编号、软换行与注释需要上下文，只在 `keyed_units` 中生效。
+    pub fn key<'t>(&self, unit: &'t str) -> Cow<'t, str> {
+        if !self.folds_chars() {
+            return Cow::Borrowed(unit);
+        }
+
+        let normalized: Cow<str> = match self.unicode_form {
+            Some(UnicodeForm::Nfc) => Cow::Owned(unit.nfc().collect()),
+            Some(UnicodeForm::Nfkc) => Cow::Owned(unit.nfkc().collect()),
+            None => Cow::Borrowed(unit),
+        };
+
+        let mut key = String::with_capacity(normalized.len());
+        let mut pending_space = false;
+
+        for c in normalized.chars() {
+            let c = if self.fold_width { fold_width(c) } else { c };
+            let c = if self.fold_chinese_variants {
+                traditional_to_simplified().get(&c).copied().unwrap_or(c)
+            } else {
+                c
+            };
+
+            if self.ignore_punctuation && !(c.is_alphanumeric() || c.is_whitespace() || is_cjk_ideograph(c)) {
+                continue;
+            }
+
+            if self.ignore_whitespace && c.is_whitespace() {
+                pending_space = !key.is_empty();
+                continue;
+            }
+
+            if pending_space {
+                key.push(' ');
+                pending_space = false;
+            }
+
+            if self.ignore_case {
+                key.extend(c.to_lowercase());
+            } else {
+                key.push(c);
+            }
+        }
+
+        Cow::Owned(key)
+    }
+
+    /// 计算每个比较单元的比较键
+    ///
+    /// 比较键为空的单元（被忽略的空白、标点）并入前一个单元，开头的并入第一个单元，
+    /// 因此返回的区间仍首尾相接；整段文本都可忽略时返回空列表。
+    pub fn keyed_units<'t>(
+        &self,
+        text: &'t str,
+        segments: &[Range<usize>],
+    ) -> (Vec<Range<usize>>, Vec<Cow<'t, str>>) {
+        if self.is_identity() {
+            let keys = segments.iter().map(|r| Cow::Borrowed(&text[r.clone()])).collect();
+            return (segments.to_vec(), keys);
+        }
+
+        let masks = self.masks(text);
+        let mut units: Vec<Range<usize>> = Vec::with_capacity(segments.len());
+        let mut keys = Vec::with_capacity(segments.len());
+        let mut leading = None;
+
+        for segment in segments {
+            let key = match apply_masks(text, segment, &masks) {
+                Cow::Borrowed(unit) => self.key(unit),
+                Cow::Owned(unit) => Cow::Owned(self.key(&unit).into_owned()),
+            };
+
+            if key.is_empty() {
+                match units.last_mut() {
+                    Some(unit) => unit.end = segment.end,
+                    None => {
+                        leading.get_or_insert(segment.start);
+                    }
+                }
+            } else {
+                units.push(leading.take().unwrap_or(segment.start)..segment.end);
+                keys.push(key);
+            }
+        }
+
+        (units, keys)
+    }
+
+    /// 按起点排序、互不重叠的屏蔽区间
+    fn masks(&self, text: &str) -> Vec<Mask> {
+        let mut masks: Vec<Mask> = Vec::new();
+
+        masks.extend(self.rules.ignored(text).into_iter().map(|range| (range, Cow::Borrowed(""))));
+        masks.extend(self.rules.canonical(text).into_iter().map(|(range, canonical)| (range, Cow::Owned(canonical))));
+
+        if self.ignore_list_numbering {
+            masks.extend(
+                list_marker()
+                    .captures_iter(text)
+                    .filter_map(|captures| captures.get(1))
+                    .map(|marker| (marker.range(), Cow::Borrowed(""))),
+            );
+        }
+
+        if self.ignore_reflow {
+            masks.extend(soft_breaks(text));
+        }
+
+        if let Some(language) = self.ignore_comments {
+            masks.extend(code::comment_ranges(text, language).into_iter().map(|range| (range, Cow::Borrowed(""))));
+        }
+
+        masks.sort_by_key(|(range, _)| (range.start, std::cmp::Reverse(range.end)));
+        let mut merged: Vec<Mask> = Vec::with_capacity(masks.len());
+        for mask in masks {
+            match merged.last() {
+                Some((last, _)) if mask.0.start < last.end => {}
+                _ => merged.push(mask),
+            }
+        }
+        merged
+    }
+}
+
+/// 软换行：前后两行都有内容、下一行不是列表项的换行，连同两侧的行内空白视为一个空格；
+/// 两侧都是中文字符时直接连接
+fn soft_breaks(text: &str) -> Vec<Mask> {
+    let mut masks = Vec::new();
+    let bytes = text.as_bytes();
+
+    for (newline, _) in text.match_indices('\n') {
+        let before = text[..newline].trim_end_matches([' ', '\t', '\r']);
+        let after = text[newline + 1..].trim_start_matches([' ', '\t']);
+        let previous_line = before.rsplit('\n').next().unwrap_or_default();
+        let next_line = after.split('\n').next().unwrap_or_default().trim_end_matches('\r');
+
+        if previous_line.trim().is_empty()
+            || next_line.trim().is_empty()
+            || is_bullet(next_line)
+            || list_marker().find(next_line).is_some_and(|marker| marker.start() == 0)
+        {
+            continue;
+        }
+
+        let start = before.len();
+        let end = bytes.len() - after.len();
+        let joins_chinese = before.chars().next_back().is_some_and(is_chinese_char)
+            && after.chars().next().is_some_and(is_chinese_char);
+        masks.push((start..end, Cow::Borrowed(if joins_chinese { "" } else { " " })));
+    }
+
+    masks
+}
+
+/// 按屏蔽区间改写单元；区间起点所在的单元写入替代文本，其余部分略去
+fn apply_masks<'t>(text: &'t str, segment: &Range<usize>, masks: &[Mask]) -> Cow<'t, str> {
+    let first = masks.partition_point(|(range, _)| range.end <= segment.start);
+    let overlapping = masks[first..].iter().take_while(|(range, _)| range.start < segment.end);
+
+    let mut unit = String::new();
+    let mut position = segment.start;
+    let mut masked = false;
+    for (range, replacement) in overlapping {
+        masked = true;
+        if range.start > position {
+            unit.push_str(&text[position..range.start]);
+        }
+        if range.start >= segment.start {
+            unit.push_str(replacement);
+        }
+        position = position.max(range.end);
+    }
+
+    if !masked {
+        return Cow::Borrowed(&text[segment.clone()]);
+    }
+    if position < segment.end {
+        unit.push_str(&text[position..segment.end]);
+    }
+    Cow::Owned(unit)
+}
+
+/// 按字符切分，组合附加符号并入前一个字符，使规范化前后的字符一一对应
+pub fn segment_clusters(text: &str) -> Vec<Range<usize>> {
+    let mut clusters: Vec<Range<usize>> = Vec::with_capacity(text.len());
+
+    for (i, c) in text.char_indices() {
+        let end = i + c.len_utf8();
+        match clusters.last_mut() {
+            Some(cluster) if is_combining_mark(c) => cluster.end = end,
+            _ => clusters.push(i..end),
+        }
+    }
+
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ignored_units_are_merged_into_neighbours() {
+        let rules = CompiledRules::default();
+        let normalizer = Normalizer::new(&DiffOptions {
+            ignore_case: true,
+            ignore_whitespace: true,
+            ignore_punctuation: true,
+            ..DiffOptions::default()
+        }, &rules);
+        assert_eq!(normalizer.key("  Hello,\n  World!  "), "hello world");
+
+        let text = " A, b";
+        let segments: Vec<Range<usize>> = (0..text.len()).map(|i| i..i + 1).collect();
+        let (units, keys) = normalizer.keyed_units(text, &segments);
+        assert_eq!(units, vec![0..4, 4..5]);
+        assert_eq!(keys, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_unicode_width_and_variant_folding() {
+        let rules = CompiledRules::default();
+        let normalizer = Normalizer::new(&DiffOptions {
+            unicode_form: Some(UnicodeForm::Nfc),
+            fold_width: true,
+            fold_chinese_variants: true,
+            ..DiffOptions::default()
+        }, &rules);
+        assert_eq!(normalizer.key("Cafe\u{301}"), normalizer.key("Café"));
+        assert_eq!(normalizer.key("ＡＢＣ１２３（甲方）"), "ABC123(甲方)");
+        assert_eq!(normalizer.key("違約條款"), normalizer.key("违约条款"));
+        assert_eq!(segment_clusters("e\u{301}x").len(), 2);
+    }
+
+    #[test]
+    fn test_masks_hide_numbering_patterns_and_soft_breaks() {
+        let options = DiffOptions {
+            ignore_patterns: vec![r"\d{4}-\d{2}-\d{2}".to_string()],
+            ignore_reflow: true,
+            ignore_list_numbering: true,
+            ..DiffOptions::default()
+        };
+        let rules = CompiledRules::compile(&options).unwrap();
+        let normalizer = Normalizer::new(&options, &rules);
+
+        let key = |text: &str| {
+            normalizer.keyed_units(text, &[Range { start: 0, end: text.len() }]).1.concat()
+        };
+        assert_eq!(key("3. 更新于 2024-01-01"), key("1. 更新于 2025-12-31"));
+        assert_eq!(key("the quick\n  brown fox"), "the quick brown fox");
+        assert_eq!(key("中文段落\n继续"), "中文段落继续");
+        // 空行与列表项前的换行不是软换行
+        assert_eq!(key("a\n\nb"), "a\n\nb");
+        assert_eq!(key("x\n- y"), "x\n- y");
+        assert_eq!(key("(1) a\n(2) b"), "a\nb");
+    }
+}
@@ -0,0 +1,101 @@
+// 编辑操作定义，所有序列对比算法的公共输出
+use serde::{Deserialize, Serialize};
+
+/// 序列级编辑操作
+///
+/// 索引均指向原始序列（左侧为 old，右侧为 new），长度以元素个数计。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DiffOp {
+    Equal {
+        old_index: usize,
+        new_index: usize,
+        len: usize,
+    },
+    Delete {
+        old_index: usize,
+        old_len: usize,
+        new_index: usize,
+    },
+    Insert {
+        old_index: usize,
+        new_index: usize,
+        new_len: usize,
+    },
+}
+
+/// 编辑操作收集器，自动合并相邻的同类操作
+#[derive(Debug, Default)]
+pub struct OpsBuilder {
+    ops: Vec<DiffOp>,
+}
+
+impl OpsBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn equal(&mut self, old_index: usize, new_index: usize, len: usize) {
+        if len == 0 {
+            return;
+        }
+        if let Some(DiffOp::Equal { len: last_len, .. }) = self.ops.last_mut() {
+            *last_len += len;
+            return;
+        }
+        self.ops.push(DiffOp::Equal { old_index, new_index, len });
+    }
+
+    pub fn delete(&mut self, old_index: usize, old_len: usize, new_index: usize) {
+        if old_len == 0 {
+            return;
+        }
+        if let Some(DiffOp::Delete { old_len: last_len, .. }) = self.ops.last_mut() {
+            *last_len += old_len;
+            return;
+        }
+        self.ops.push(DiffOp::Delete { old_index, old_len, new_index });
+    }
+
+    pub fn insert(&mut self, old_index: usize, new_index: usize, new_len: usize) {
+        if new_len == 0 {
+            return;
+        }
+        if let Some(DiffOp::Insert { new_len: last_len, .. }) = self.ops.last_mut() {
+            *last_len += new_len;
+            return;
+        }
+        self.ops.push(DiffOp::Insert { old_index, new_index, new_len });
+    }
+
+    pub fn finish(self) -> Vec<DiffOp> {
+        self.ops
+    }
+}
+
+/// 编辑操作展开为逐个元素的配对：相等的元素两两配对，相邻的删除与新增按顺序逐个配对，多出的只有一侧
+pub fn pair_elements(ops: &[DiffOp]) -> Vec<(Option<usize>, Option<usize>)> {
+    let mut pairs = Vec::new();
+    let mut index = 0;
+
+    while index < ops.len() {
+        if let DiffOp::Equal { old_index, new_index, len } = ops[index] {
+            pairs.extend((0..len).map(|k| (Some(old_index + k), Some(new_index + k))));
+            index += 1;
+            continue;
+        }
+
+        // 收集连续的删除与新增
+        let (mut removed, mut added) = (Vec::new(), Vec::new());
+        while let Some(op) = ops.get(index) {
+            match *op {
+                DiffOp::Delete { old_index, old_len, .. } => removed.extend(old_index..old_index + old_len),
+                DiffOp::Insert { new_index, new_len, .. } => added.extend(new_index..new_index + new_len),
+                DiffOp::Equal { .. } => break,
+            }
+            index += 1;
+        }
+        pairs.extend((0..removed.len().max(added.len())).map(|k| (removed.get(k).copied(), added.get(k).copied())));
+    }
+
+    pairs
+}
@@ -0,0 +1,322 @@
+// 锚点分区：以两侧各只出现一次的比较单元（行，分段、分句模式下为段落或句子）为锚点
+// 把两侧切成相互对应的区间，供流式对比逐块输出、并行对比分别计算
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rayon::prelude::*;
+
+use super::algorithm::intern;
+use super::patience;
+use super::position::TextCursor;
+use super::{
+    adopt_inline_changes, DiffEngine, DiffItem, DiffMetadata, DiffResult, DiffType, Level, LineRange, Position,
+    TextRange,
+};
+
+/// 分区对比时每个区间的最少比较单元数，过小的区间调度开销大于收益
+const MIN_REGION_UNITS: usize = 1000;
+
+/// 分区对比的最少区间数，保证进度报告足够及时
+const MIN_REGIONS: usize = 32;
+
+/// 两侧相互对应的一对区间（原文字节区间）
+pub struct Region {
+    pub left: Range<usize>,
+    pub right: Range<usize>,
+}
+
+/// 切分区间所依据的比较单元：分段、分句模式下为段落或句子，否则为行
+fn cut_segments(engine: &DiffEngine, text: &str, has_chinese: bool) -> Vec<Range<usize>> {
+    if engine.options.split_by_paragraph {
+        engine.segment(text, Level::Paragraph, has_chinese)
+    } else if engine.options.split_by_sentence {
+        engine.segment(text, Level::Sentence, has_chinese)
+    } else {
+        engine.split_after(text, |c| c == '\n')
+    }
+}
+
+/// 按锚点单元切分区间
+///
+/// 锚点为两侧比较键各只出现一次的比较单元，与整体对比时的单元与比较键一致，
+/// 因此切点只落在段落、句子（或行）的边界上。自上一个切点起任一侧累计满 `region_units` 个单元后，
+/// 在下一个锚点之前切开；锚点两侧相同，因此每个区间两侧的内容相互对应。锚点稀疏时区间会更大。
+pub fn plan_regions(engine: &DiffEngine, left: &str, right: &str, region_units: usize) -> Vec<Region> {
+    let has_chinese = engine.contains_chinese(left) || engine.contains_chinese(right);
+    let normalizer = engine.normalizer();
+    let (left_units, left_keys) = normalizer.keyed_units(left, &cut_segments(engine, left, has_chinese));
+    let (right_units, right_keys) = normalizer.keyed_units(right, &cut_segments(engine, right, has_chinese));
+    let left_refs: Vec<&str> = left_keys.iter().map(|key| key.as_ref()).collect();
+    let right_refs: Vec<&str> = right_keys.iter().map(|key| key.as_ref()).collect();
+    let (left_ids, right_ids) = intern(&left_refs, &right_refs);
+
+    let region_units = region_units.max(1);
+    let mut regions = Vec::new();
+    let (mut left_cut, mut right_cut) = (0, 0);
+    let (mut left_start, mut right_start) = (0, 0);
+
+    for (i, j) in patience::anchors(&left_ids, &right_ids) {
+        if i - left_cut >= region_units || j - right_cut >= region_units {
+            regions.push(Region {
+                left: left_start..left_units[i].start,
+                right: right_start..right_units[j].start,
+            });
+            (left_cut, right_cut) = (i, j);
+            (left_start, right_start) = (left_units[i].start, right_units[j].start);
+        }
+    }
+
+    regions.push(Region {
+        left: left_start..left.len(),
+        right: right_start..right.len(),
+    });
+
+    regions
+}
+
+/// 把各区间内算出的差异项换算为全文坐标并连续编号，区间须按顺序放入；
+/// 区间首个相等项与已输出的末个相等项首尾相接时合并为一项
+pub struct Stitcher<'a> {
+    left_cursor: TextCursor<'a>,
+    right_cursor: TextCursor<'a>,
+    next_id: usize,
+}
+
+impl<'a> Stitcher<'a> {
+    pub fn new(left: &'a str, right: &'a str) -> Self {
+        Self {
+            left_cursor: TextCursor::new(left),
+            right_cursor: TextCursor::new(right),
+            next_id: 0,
+        }
+    }
+
+    pub fn place(&mut self, region: &Region, items: Vec<DiffItem>, out: &mut Vec<DiffItem>) {
+        let base = Position {
+            left: self.left_cursor.range(region.left.start, region.left.start),
+            right: self.right_cursor.range(region.right.start, region.right.start),
+        };
+
+        for (index, mut item) in items.into_iter().enumerate() {
+            item.position.left = item.position.left.offset_by(&base.left);
+            item.position.right = item.position.right.offset_by(&base.right);
+
+            let continues_equal = index == 0 && is_plain_equal(&item);
+            if let Some(last) = out.last_mut().filter(|last| continues_equal && is_plain_equal(last)) {
+                last.content.push_str(&item.content);
+                extend_range(&mut last.position.left, &item.position.left);
+                extend_range(&mut last.position.right, &item.position.right);
+                extend_lines(&mut last.left_line, item.left_line.map(|lines| lines.offset_by(base.left.start_line)));
+                extend_lines(&mut last.right_line, item.right_line.map(|lines| lines.offset_by(base.right.start_line)));
+                continue;
+            }
+
+            item.id = format!("diff-{}", self.next_id);
+            self.next_id += 1;
+
+            let base_line = match item.diff_type {
+                DiffType::Add | DiffType::Move => base.right.start_line,
+                _ => base.left.start_line,
+            };
+            item.line_number = item.line_number.map(|line| line + base_line - 1);
+            item.left_line = item.left_line.map(|lines| lines.offset_by(base.left.start_line));
+            item.right_line = item.right_line.map(|lines| lines.offset_by(base.right.start_line));
+
+            let id = item.id.clone();
+            adopt_inline_changes(&mut item.inline_changes, &id, &base);
+            out.push(item);
+        }
+    }
+}
+
+/// 两侧原文相同的相等项
+fn is_plain_equal(item: &DiffItem) -> bool {
+    item.diff_type == DiffType::Equal && item.original_content.is_none()
+}
+
+fn extend_range(range: &mut TextRange, next: &TextRange) {
+    range.byte_end = next.byte_end;
+    range.char_end = next.char_end;
+    range.end_line = next.end_line;
+    range.end_column = next.end_column;
+}
+
+fn extend_lines(lines: &mut Option<LineRange>, next: Option<LineRange>) {
+    if let (Some(lines), Some(next)) = (lines, next) {
+        lines.end = next.end;
+    }
+}
+
+/// 分区并行对比
+///
+/// 各区间在多线程中计算；每个区间开始前检查取消、完成后报告进度，
+/// 取消后剩余区间不再计算。移动检测限于区间内。
+pub fn partitioned_diff(engine: &DiffEngine, left: &str, right: &str) -> DiffResult {
+    let has_chinese = engine.contains_chinese(left) || engine.contains_chinese(right);
+    let units = cut_segments(engine, left, has_chinese).len().max(cut_segments(engine, right, has_chinese).len());
+    let region_count = (rayon::current_num_threads() * 4).max(MIN_REGIONS);
+    let regions = plan_regions(engine, left, right, (units / region_count).max(MIN_REGION_UNITS));
+
+    let total = left.len() + right.len();
+    let processed = AtomicUsize::new(0);
+    let diff_region = |region: &Region| -> Vec<DiffItem> {
+        if engine.is_cancelled() {
+            return Vec::new();
+        }
+
+        let result = engine.diff_part(&left[region.left.clone()], &right[region.right.clone()], has_chinese);
+        let size = region.left.len() + region.right.len();
+        engine.report_progress(processed.fetch_add(size, Ordering::Relaxed) + size, total);
+        result.items
+    };
+
+    let region_items: Vec<Vec<DiffItem>> = regions.par_iter().map(diff_region).collect();
+
+    let mut stitcher = Stitcher::new(left, right);
+    let mut items = Vec::new();
+    for (region, region_items) in regions.iter().zip(region_items) {
+        stitcher.place(region, region_items, &mut items);
+    }
+
+    let stats = engine.calculate_stats(&items, left, right);
+
+    DiffResult {
+        items,
+        stats,
+        metadata: DiffMetadata {
+            algorithm: engine.algorithm(),
+            has_chinese,
+            approximate: false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    use crate::diff_engine::{CancellationToken, DiffError, DiffOptions, DiffProgress};
+
+    #[test]
+    fn test_regions_follow_anchors_across_insertions() {
+        let left: String = (0..30).map(|i| format!("第{}行\n", i)).collect();
+        let right = format!("开头新增\n{}", left);
+
+        let engine = DiffEngine::new(DiffOptions::default());
+        let regions = plan_regions(&engine, &left, &right, 10);
+        assert_eq!(regions.len(), 4);
+
+        // 右侧每个区间都比左侧晚一行开始，内容保持对应
+        for region in &regions[1..] {
+            assert_eq!(&left[region.left.clone()], &right[region.right.clone()]);
+        }
+        assert_eq!(regions[0].left.start, 0);
+        assert_eq!(regions[3].right.end, right.len());
+    }
+
+    #[test]
+    fn test_parallel_diff_matches_whole_diff() {
+        let left: String = (0..3000).map(|i| format!("第{}行内容\n", i)).collect();
+        let right = left
+            .replacen("第10行内容", "第10行修改", 1)
+            .replacen("第1500行内容\n", "", 1)
+            .replacen("第2999行内容", "第2999行内容\n末尾新增", 1);
+
+        let engine = DiffEngine::new(DiffOptions {
+            parallel: true,
+            ..DiffOptions::default()
+        });
+        let parallel = partitioned_diff(&engine, &left, &right);
+        let whole = engine.diff_whole(&left, &right);
+
+        // 区间边界处等价的对齐方式可能不同，比较统计并由结果还原两侧原文
+        assert_eq!(parallel.stats.additions, whole.stats.additions);
+        assert_eq!(parallel.stats.deletions, whole.stats.deletions);
+        let (mut left_text, mut right_text) = (String::new(), String::new());
+        for (index, item) in parallel.items.iter().enumerate() {
+            assert_eq!(item.id, format!("diff-{}", index));
+            let (left_range, right_range) = (&item.position.left, &item.position.right);
+            left_text.push_str(&left[left_range.byte_start..left_range.byte_end]);
+            right_text.push_str(&right[right_range.byte_start..right_range.byte_end]);
+            if item.diff_type != DiffType::Remove {
+                assert_eq!(&right[right_range.byte_start..right_range.byte_end], item.content);
+            }
+        }
+        assert_eq!((left_text, right_text), (left, right));
+        assert_eq!(parallel.stats.similarity, whole.stats.similarity);
+    }
+
+    #[test]
+    fn test_partitioned_segment_diff_matches_whole_diff() {
+        // 段落、句子都跨行：按行切分会把它们切成两半
+        let paragraphs: String = (0..3000).map(|i| format!("第{}段第一行，\n第{}段第二行。\n\n", i, i)).collect();
+        let sentences: String = (0..3000).map(|i| format!("第{}句前半，\n第{}句后半。", i, i)).collect();
+        let edited_paragraphs = paragraphs
+            .replacen("第10段第二行", "第10段第二行有改动", 1)
+            .replacen("第1500段第一行，\n第1500段第二行。\n\n", "", 1)
+            .replacen("第2999段第二行。\n\n", "第2999段第二行。\n\n末尾新增一段。\n", 1);
+        let edited_sentences = sentences
+            .replacen("第10句后半", "第10句后半有改动", 1)
+            .replacen("第1500句前半，\n第1500句后半。", "", 1)
+            .replacen("第2999句后半。", "第2999句后半。末尾新增一句。", 1);
+
+        for (left, right, split_by_paragraph) in [
+            (&paragraphs, &edited_paragraphs, true),
+            (&sentences, &edited_sentences, false),
+        ] {
+            let engine = DiffEngine::new(DiffOptions {
+                parallel: true,
+                split_by_paragraph,
+                split_by_sentence: !split_by_paragraph,
+                ..DiffOptions::default()
+            });
+            assert!(plan_regions(&engine, left, right, MIN_REGION_UNITS).len() > 1);
+
+            let partitioned = partitioned_diff(&engine, left, right);
+            let whole = engine.diff_whole(left, right);
+            assert_eq!(serde_json::to_value(&partitioned).unwrap(), serde_json::to_value(&whole).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_progress_is_reported_per_region_and_cancel_stops_early() {
+        let left: String = (0..12000).map(|i| format!("第{}行内容\n", i)).collect();
+        let right = left.replacen("第6000行内容", "第6000行修改", 1);
+        let parallel = DiffOptions {
+            parallel: true,
+            ..DiffOptions::default()
+        };
+
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let sink = reports.clone();
+        let engine = DiffEngine::new(parallel.clone())
+            .with_progress(move |progress: DiffProgress| sink.lock().unwrap().push(progress.processed));
+        engine.try_compute_diff(&left, &right).unwrap();
+
+        // 各区间在不同线程中报告，按已处理长度排序后应严格递增
+        let mut processed = reports.lock().unwrap().clone();
+        processed.sort_unstable();
+        assert!(processed.len() > 1);
+        assert!(processed.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(processed.last(), Some(&(left.len() + right.len())));
+
+        // 非并行模式下观察进度不改变结果：整体对比，完成时报告一次
+        reports.lock().unwrap().clear();
+        let sink = reports.clone();
+        let observed = DiffEngine::new(DiffOptions::default())
+            .with_progress(move |progress: DiffProgress| sink.lock().unwrap().push(progress.processed))
+            .compute_diff(&left, &right);
+        let plain = DiffEngine::new(DiffOptions::default()).compute_diff(&left, &right);
+        assert_eq!(*reports.lock().unwrap(), vec![left.len() + right.len()]);
+        assert_eq!(serde_json::to_value(&observed).unwrap(), serde_json::to_value(&plain).unwrap());
+
+        // 第一个区间完成后即取消
+        let token = CancellationToken::new();
+        let trigger = token.clone();
+        let engine = DiffEngine::new(parallel)
+            .with_cancellation(token)
+            .with_progress(move |_| trigger.cancel());
+        assert_eq!(engine.try_compute_diff(&left, &right).unwrap_err(), DiffError::Cancelled);
+    }
+}
@@ -0,0 +1,452 @@
+// 补丁：把对比结果输出为统一格式（unified diff），并把补丁应用回文本
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{DiffItem, DiffResult, DiffType};
+
+#[derive(Error, Debug)]
+pub enum PatchError {
+    #[error("补丁格式错误（第 {line} 行）: {message}")]
+    Malformed { line: usize, message: String },
+
+    #[error("第 {0} 个补丁块无法应用：找不到匹配的上下文")]
+    HunkFailed(usize),
+}
+
+/// 补丁输出选项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PatchOptions {
+    /// 每个补丁块前后保留的上下文行数
+    pub context_lines: usize,
+    pub left_label: String,
+    pub right_label: String,
+}
+
+impl Default for PatchOptions {
+    fn default() -> Self {
+        Self {
+            context_lines: 3,
+            left_label: "a".to_string(),
+            right_label: "b".to_string(),
+        }
+    }
+}
+
+const NO_NEWLINE_MARKER: &str = "\\ No newline at end of file";
+
+/// 模糊匹配时最多忽略的首尾上下文行数（与 GNU patch 的默认值相同）
+const MAX_FUZZ: usize = 2;
+
+/// 补丁块的应用情况
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppliedHunk {
+    /// 实际应用位置相对块头行号的偏移（行）
+    pub offset: isize,
+    /// 为找到匹配而忽略的首尾上下文行数
+    pub fuzz: usize,
+}
+
+/// 应用补丁的结果：应用后的文本与各补丁块的应用情况
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedPatch {
+    pub text: String,
+    pub hunks: Vec<AppliedHunk>,
+}
+
+/// 行级编辑：两侧未改动的行、仅左侧的行、仅右侧的行（行号从 0 开始）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Line {
+    Context(usize, usize),
+    Removed(usize),
+    Added(usize),
+}
+
+impl DiffResult {
+    /// 输出为统一格式补丁
+    ///
+    /// 整行落在同一相等项内、且在另一侧恰好对应一整行的行作为上下文，其余行视为改动，
+    /// 因此任意粒度（字符、词、句、段）的结果都能转换为行级补丁。
+    pub fn to_unified_diff(&self, left: &str, right: &str, options: &PatchOptions) -> String {
+        let left_lines: Vec<&str> = left.split_inclusive('\n').collect();
+        let right_lines: Vec<&str> = right.split_inclusive('\n').collect();
+
+        let lines = align_lines(&context_pairs(&self.items, &left_lines, &right_lines), left_lines.len(), right_lines.len());
+        let mut patch = format!("--- {}\n+++ {}\n", options.left_label, options.right_label);
+
+        for hunk in group_hunks(&lines, options.context_lines) {
+            let (old_start, new_start) = (hunk_start(&lines, &hunk, true), hunk_start(&lines, &hunk, false));
+            let hunk_lines = &lines[hunk];
+            let old_count = hunk_lines.iter().filter(|line| !matches!(line, Line::Added(_))).count();
+            let new_count = hunk_lines.iter().filter(|line| !matches!(line, Line::Removed(_))).count();
+
+            patch.push_str(&format!("@@ -{},{} +{},{} @@\n", old_start, old_count, new_start, new_count));
+
+            for line in hunk_lines {
+                let (prefix, text) = match *line {
+                    Line::Context(i, _) => (' ', left_lines[i]),
+                    Line::Removed(i) => ('-', left_lines[i]),
+                    Line::Added(j) => ('+', right_lines[j]),
+                };
+                patch.push(prefix);
+                patch.push_str(text);
+                if !text.ends_with('\n') {
+                    patch.push('\n');
+                    patch.push_str(NO_NEWLINE_MARKER);
+                    patch.push('\n');
+                }
+            }
+        }
+
+        patch
+    }
+}
+
+/// 找出两侧原样对应的整行，按顺序返回 (左侧行号, 右侧行号)，行号从 0 开始
+pub(super) fn context_pairs(items: &[DiffItem], left_lines: &[&str], right_lines: &[&str]) -> Vec<(usize, usize)> {
+    let line_starts = |lines: &[&str]| -> Vec<usize> {
+        lines
+            .iter()
+            .scan(0, |offset, line| {
+                let start = *offset;
+                *offset += line.len();
+                Some(start)
+            })
+            .collect()
+    };
+    let left_starts = line_starts(left_lines);
+    let right_starts = line_starts(right_lines);
+
+    let mut pairs = Vec::new();
+    // 忽略选项下两侧文本不同的相等项不能作为上下文
+    let equal_items = items
+        .iter()
+        .filter(|item| item.diff_type == DiffType::Equal && item.original_content.is_none());
+
+    for item in equal_items {
+        let (left_range, right_range) = (&item.position.left, &item.position.right);
+        let first = left_starts.partition_point(|&start| start < left_range.byte_start);
+
+        for i in first..left_lines.len() {
+            let line_end = left_starts[i] + left_lines[i].len();
+            if line_end > left_range.byte_end {
+                break;
+            }
+
+            let image = right_range.byte_start + (left_starts[i] - left_range.byte_start);
+            let counterpart = right_starts
+                .binary_search(&image)
+                .ok()
+                .filter(|&j| right_lines[j].len() == left_lines[i].len());
+            if let Some(j) = counterpart {
+                pairs.push((i, j));
+            }
+        }
+    }
+
+    pairs
+}
+
+/// 以上下文行为锚点，把两侧的行对齐为行级编辑序列
+pub(super) fn align_lines(pairs: &[(usize, usize)], left_len: usize, right_len: usize) -> Vec<Line> {
+    let mut lines = Vec::with_capacity(left_len.max(right_len));
+    let (mut i, mut j) = (0, 0);
+
+    for &(left_index, right_index) in pairs.iter().chain([(left_len, right_len)].iter()) {
+        lines.extend((i..left_index).map(Line::Removed));
+        lines.extend((j..right_index).map(Line::Added));
+        if left_index < left_len {
+            lines.push(Line::Context(left_index, right_index));
+        }
+        (i, j) = (left_index + 1, right_index + 1);
+    }
+
+    lines
+}
+
+/// 按上下文行数把改动分组为补丁块，返回每块在行级编辑序列中的下标区间
+fn group_hunks(lines: &[Line], context: usize) -> Vec<Range<usize>> {
+    let mut hunks: Vec<Range<usize>> = Vec::new();
+
+    for (index, line) in lines.iter().enumerate() {
+        if matches!(line, Line::Context(..)) {
+            continue;
+        }
+
+        let start = index.saturating_sub(context);
+        let end = (index + 1 + context).min(lines.len());
+        match hunks.last_mut() {
+            Some(hunk) if start <= hunk.end => hunk.end = end,
+            _ => hunks.push(start..end),
+        }
+    }
+
+    hunks
+}
+
+/// 补丁块在某侧的起始行号（从 1 开始）
+///
+/// 该侧在块内没有任何行时，按惯例取块前最近一行的行号，文件开头为 0。
+fn hunk_start(lines: &[Line], hunk: &Range<usize>, old: bool) -> usize {
+    let line_of = |line: &Line| match (*line, old) {
+        (Line::Context(i, _), true) | (Line::Removed(i), true) => Some(i + 1),
+        (Line::Context(_, j), false) | (Line::Added(j), false) => Some(j + 1),
+        _ => None,
+    };
+
+    lines[hunk.clone()]
+        .iter()
+        .find_map(line_of)
+        .or_else(|| lines[..hunk.start].iter().rev().find_map(line_of))
+        .unwrap_or(0)
+}
+
+/// 解析后的补丁块
+struct Hunk {
+    old_start: usize,
+    old_lines: Vec<String>,
+    new_lines: Vec<String>,
+    /// 块首、块尾连续的上下文行数
+    leading_context: usize,
+    trailing_context: usize,
+}
+
+/// 把统一格式补丁应用到文本
+pub fn apply_patch(text: &str, patch: &str) -> Result<String, PatchError> {
+    apply_patch_detailed(text, patch).map(|applied| applied.text)
+}
+
+/// 把统一格式补丁应用到文本，并报告每个补丁块的偏移量与模糊匹配行数
+///
+/// 补丁块优先在标注的行号处应用；上下文不匹配时向前后搜索最近的匹配位置，
+/// 并把偏移量带入后续补丁块。仍找不到时依次忽略首尾各 1、2 行上下文再搜索（模糊匹配），
+/// 被忽略的上下文行保留目标文本中的内容。
+pub fn apply_patch_detailed(text: &str, patch: &str) -> Result<AppliedPatch, PatchError> {
+    let hunks = parse_patch(patch)?;
+    let source: Vec<&str> = text.split_inclusive('\n').collect();
+
+    let mut output = String::with_capacity(text.len());
+    let mut applied = Vec::with_capacity(hunks.len());
+    let mut cursor = 0;
+    let mut delta: isize = 0;
+
+    for (index, hunk) in hunks.iter().enumerate() {
+        // 旧行数为 0 时行号指向插入点之前的一行
+        let nominal = if hunk.old_lines.is_empty() {
+            hunk.old_start
+        } else {
+            hunk.old_start.saturating_sub(1)
+        };
+        let expected = (nominal as isize + delta).max(cursor as isize) as usize;
+
+        let (fuzz, leading, trailing, position) = (0..=MAX_FUZZ)
+            // 上下文不足时更大的模糊度不会多忽略行，无需重复搜索
+            .filter(|&fuzz| fuzz == 0 || hunk.leading_context >= fuzz || hunk.trailing_context >= fuzz)
+            .find_map(|fuzz| {
+                let leading = hunk.leading_context.min(fuzz);
+                let trailing = hunk.trailing_context.min(fuzz).min(hunk.old_lines.len() - leading);
+                let old_lines = &hunk.old_lines[leading..hunk.old_lines.len() - trailing];
+                let position = find_hunk(&source, old_lines, cursor, expected + leading)?;
+                Some((fuzz, leading, trailing, position))
+            })
+            .ok_or(PatchError::HunkFailed(index + 1))?;
+
+        output.extend(source[cursor..position].iter().copied());
+        output.extend(hunk.new_lines[leading..hunk.new_lines.len() - trailing].iter().map(String::as_str));
+        cursor = position + hunk.old_lines.len() - leading - trailing;
+        delta = position as isize - leading as isize - nominal as isize;
+        applied.push(AppliedHunk { offset: delta, fuzz });
+    }
+
+    output.extend(source[cursor..].iter().copied());
+    Ok(AppliedPatch {
+        text: output,
+        hunks: applied,
+    })
+}
+
+/// 在 `[from, 末尾]` 内寻找与旧行完全一致且离 `expected` 最近的位置
+fn find_hunk(source: &[&str], old_lines: &[String], from: usize, expected: usize) -> Option<usize> {
+    let last = source.len().checked_sub(old_lines.len())?;
+    if from > last {
+        return None;
+    }
+
+    let matches_at = |position: usize| {
+        source[position..position + old_lines.len()]
+            .iter()
+            .zip(old_lines)
+            .all(|(line, old)| *line == old)
+    };
+
+    let expected = expected.clamp(from, last);
+    (0..=last - from)
+        .flat_map(|distance| [expected.checked_add(distance), expected.checked_sub(distance)])
+        .flatten()
+        .filter(|&position| position >= from && position <= last)
+        .find(|&position| matches_at(position))
+}
+
+fn parse_patch(patch: &str) -> Result<Vec<Hunk>, PatchError> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    // 当前块尚未读取的旧行、新行数
+    let mut remaining = (0, 0);
+    // 上一行属于旧行、新行还是两者，用于处理“无结尾换行”标记
+    let mut last_sides = (false, false);
+
+    for (index, line) in patch.split_inclusive('\n').enumerate() {
+        let line_number = index + 1;
+        let malformed = |message: &str| PatchError::Malformed {
+            line: line_number,
+            message: message.to_string(),
+        };
+
+        if line.starts_with("@@") {
+            if remaining != (0, 0) {
+                return Err(malformed("上一个补丁块行数不足"));
+            }
+            let (old_start, old_count, new_count) =
+                parse_hunk_header(line).ok_or_else(|| malformed("无法解析补丁块头"))?;
+            hunks.push(Hunk {
+                old_start,
+                old_lines: Vec::new(),
+                new_lines: Vec::new(),
+                leading_context: 0,
+                trailing_context: 0,
+            });
+            remaining = (old_count, new_count);
+            continue;
+        }
+
+        let Some(hunk) = hunks.last_mut() else {
+            // 第一个补丁块之前是文件头
+            continue;
+        };
+
+        if line.starts_with(NO_NEWLINE_MARKER) {
+            // 上一行在原文中没有结尾换行
+            let (old, new) = last_sides;
+            for (lines, applies) in [(&mut hunk.old_lines, old), (&mut hunk.new_lines, new)] {
+                if let Some(last) = lines.last_mut().filter(|last| applies && last.ends_with('\n')) {
+                    last.pop();
+                }
+            }
+            continue;
+        }
+
+        if remaining == (0, 0) {
+            // 块外的内容（如下一个文件头）忽略
+            continue;
+        }
+
+        // 部分工具会把空的上下文行输出为单独的换行
+        let content = if line == "\n" { line } else { line.get(1..).unwrap_or_default() }.to_string();
+        match line.chars().next() {
+            Some(' ') | Some('\n') if remaining.0 > 0 && remaining.1 > 0 => {
+                // 块内尚无改动行时属于块首上下文
+                if hunk.old_lines.len() == hunk.leading_context && hunk.new_lines.len() == hunk.leading_context {
+                    hunk.leading_context += 1;
+                }
+                hunk.trailing_context += 1;
+                hunk.old_lines.push(content.clone());
+                hunk.new_lines.push(content);
+                remaining = (remaining.0 - 1, remaining.1 - 1);
+                last_sides = (true, true);
+            }
+            Some('-') if remaining.0 > 0 => {
+                hunk.old_lines.push(content);
+                hunk.trailing_context = 0;
+                remaining.0 -= 1;
+                last_sides = (true, false);
+            }
+            Some('+') if remaining.1 > 0 => {
+                hunk.new_lines.push(content);
+                hunk.trailing_context = 0;
+                remaining.1 -= 1;
+                last_sides = (false, true);
+            }
+            _ => return Err(malformed("补丁块内容与块头行数不符")),
+        }
+    }
+
+    if remaining != (0, 0) {
+        return Err(PatchError::Malformed {
+            line: patch.lines().count(),
+            message: "补丁不完整".to_string(),
+        });
+    }
+
+    Ok(hunks)
+}
+
+/// 解析 `@@ -a,b +c,d @@`，省略的行数默认为 1
+fn parse_hunk_header(line: &str) -> Option<(usize, usize, usize)> {
+    let mut parts = line.split_whitespace().skip(1);
+    let old = parts.next()?.strip_prefix('-')?;
+    let new = parts.next()?.strip_prefix('+')?;
+
+    let parse = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_count) = parse(old)?;
+    let (_, new_count) = parse(new)?;
+
+    Some((old_start, old_count, new_count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff_engine::{DiffEngine, DiffOptions};
+
+    #[test]
+    fn test_patch_round_trip_with_offset() {
+        let left = "第一条\n第二条 甲方\n第三条\n第四条\n第五条\n第六条\n第七条\n第八条 乙方\n第九条";
+        let right = "第一条\n第二条 甲方（买方）\n第三条\n第四条\n第五条\n第六条\n第七条\n第八条 乙方\n第九条 附则\n";
+
+        let result = DiffEngine::new(DiffOptions::default()).compute_diff(left, right);
+        let patch = result.to_unified_diff(left, right, &PatchOptions {
+            context_lines: 1,
+            ..PatchOptions::default()
+        });
+        assert_eq!(
+            patch,
+            "--- a\n+++ b\n\
+             @@ -1,3 +1,3 @@\n 第一条\n-第二条 甲方\n+第二条 甲方（买方）\n 第三条\n\
+             @@ -8,2 +8,2 @@\n 第八条 乙方\n-第九条\n\\ No newline at end of file\n+第九条 附则\n"
+        );
+        assert_eq!(apply_patch(left, &patch).unwrap(), right);
+
+        // 文本开头多出两行时，补丁块按偏移量定位
+        let shifted = format!("前言\n目录\n{}", left);
+        assert_eq!(apply_patch(&shifted, &patch).unwrap(), format!("前言\n目录\n{}", right));
+
+        assert!(matches!(apply_patch("无关内容\n", &patch), Err(PatchError::HunkFailed(1))));
+    }
+
+    #[test]
+    fn test_fuzz_ignores_changed_context_lines() {
+        let left = "第一条\n第二条 甲方\n第三条\n第四条\n第五条\n";
+        let right = "第一条\n第二条 甲方（买方）\n第三条\n第四条\n第五条\n";
+        let result = DiffEngine::new(DiffOptions::default()).compute_diff(left, right);
+        let patch = result.to_unified_diff(left, right, &PatchOptions::default());
+
+        // 目标文本开头多一行，且补丁块的一行上下文已被改动
+        let target = "前言\n第一条（修订）\n第二条 甲方\n第三条\n第四条\n第五条\n";
+        let applied = apply_patch_detailed(target, &patch).unwrap();
+        assert_eq!(applied.text, "前言\n第一条（修订）\n第二条 甲方（买方）\n第三条\n第四条\n第五条\n");
+        assert_eq!(applied.hunks, vec![AppliedHunk { offset: 1, fuzz: 1 }]);
+
+        let exact = apply_patch_detailed(left, &patch).unwrap();
+        assert_eq!(exact.hunks, vec![AppliedHunk { offset: 0, fuzz: 0 }]);
+
+        // 改动的上下文多于可忽略的行数时仍然失败
+        let target = "第一条（修订）\n第二条 甲方\n第三条（修订）\n第四条（修订）\n第五条\n";
+        assert!(matches!(apply_patch(target, &patch), Err(PatchError::HunkFailed(1))));
+    }
+}
@@ -0,0 +1,180 @@
+// Patience Diff 算法实现
+//
+// 先以两侧都只出现一次的元素作为锚点，用耐心排序求锚点的最长递增子序列，
+// 再在锚点之间递归；找不到锚点的区间退回到 Myers（同样求解 LCS，但只需线性空间）。
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use super::control::Deadline;
+use super::myers;
+use super::ops::{DiffOp, OpsBuilder};
+
+/// 对两个序列执行 Patience Diff
+#[cfg(test)]
+pub fn diff<T: Hash + Eq>(old: &[T], new: &[T]) -> Vec<DiffOp> {
+    diff_before(old, new, &Deadline::never())
+}
+
+/// 在期限前执行 Patience Diff，期限只约束回退到的 Myers 部分
+pub fn diff_before<T: Hash + Eq>(old: &[T], new: &[T], deadline: &Deadline) -> Vec<DiffOp> {
+    let mut builder = OpsBuilder::new();
+    diff_range(old, 0, old.len(), new, 0, new.len(), deadline, &mut builder);
+    builder.finish()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn diff_range<T: Hash + Eq>(
+    old: &[T],
+    mut old_lo: usize,
+    mut old_hi: usize,
+    new: &[T],
+    mut new_lo: usize,
+    mut new_hi: usize,
+    deadline: &Deadline,
+    builder: &mut OpsBuilder,
+) {
+    // 公共前缀
+    let prefix_start = (old_lo, new_lo);
+    while old_lo < old_hi && new_lo < new_hi && old[old_lo] == new[new_lo] {
+        old_lo += 1;
+        new_lo += 1;
+    }
+    builder.equal(prefix_start.0, prefix_start.1, old_lo - prefix_start.0);
+
+    // 公共后缀（最后再输出）
+    let mut suffix_len = 0;
+    while old_lo < old_hi && new_lo < new_hi && old[old_hi - 1] == new[new_hi - 1] {
+        old_hi -= 1;
+        new_hi -= 1;
+        suffix_len += 1;
+    }
+
+    if old_lo == old_hi {
+        builder.insert(old_lo, new_lo, new_hi - new_lo);
+    } else if new_lo == new_hi {
+        builder.delete(old_lo, old_hi - old_lo, new_lo);
+    } else {
+        let anchors = unique_anchors(old, old_lo, old_hi, new, new_lo, new_hi);
+
+        if anchors.is_empty() {
+            myers::diff_range(old, old_lo..old_hi, new, new_lo..new_hi, deadline, builder);
+        } else {
+            let (mut o, mut n) = (old_lo, new_lo);
+            for (ao, an) in anchors {
+                diff_range(old, o, ao, new, n, an, deadline, builder);
+                builder.equal(ao, an, 1);
+                o = ao + 1;
+                n = an + 1;
+            }
+            diff_range(old, o, old_hi, new, n, new_hi, deadline, builder);
+        }
+    }
+
+    builder.equal(old_hi, new_hi, suffix_len);
+}
+
+/// 两侧各只出现一次的元素组成的最长锚点链，按位置递增
+pub fn anchors<T: Hash + Eq>(old: &[T], new: &[T]) -> Vec<(usize, usize)> {
+    unique_anchors(old, 0, old.len(), new, 0, new.len())
+}
+
+/// 找出区间内两侧各只出现一次的元素，返回按位置递增的最长锚点链
+fn unique_anchors<T: Hash + Eq>(
+    old: &[T],
+    old_lo: usize,
+    old_hi: usize,
+    new: &[T],
+    new_lo: usize,
+    new_hi: usize,
+) -> Vec<(usize, usize)> {
+    // (左侧出现次数, 左侧位置, 右侧出现次数, 右侧位置)
+    let mut counts: HashMap<&T, (usize, usize, usize, usize)> = HashMap::new();
+
+    for (i, item) in old[old_lo..old_hi].iter().enumerate() {
+        let entry = counts.entry(item).or_insert((0, 0, 0, 0));
+        entry.0 += 1;
+        entry.1 = old_lo + i;
+    }
+    for (j, item) in new[new_lo..new_hi].iter().enumerate() {
+        if let Some(entry) = counts.get_mut(item) {
+            entry.2 += 1;
+            entry.3 = new_lo + j;
+        }
+    }
+
+    let mut pairs: Vec<(usize, usize)> = counts
+        .into_values()
+        .filter(|&(left_count, _, right_count, _)| left_count == 1 && right_count == 1)
+        .map(|(_, left_pos, _, right_pos)| (left_pos, right_pos))
+        .collect();
+    pairs.sort_unstable();
+
+    longest_increasing_chain(&pairs)
+}
+
+/// 耐心排序求右侧位置的最长递增子序列（输入已按左侧位置排序）
+fn longest_increasing_chain(pairs: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    // 每一堆的堆顶在 pairs 中的下标
+    let mut pile_tops: Vec<usize> = Vec::new();
+    // 每个元素放入时前一堆的堆顶，用于回溯
+    let mut back_links: Vec<Option<usize>> = vec![None; pairs.len()];
+
+    for (idx, &(_, right_pos)) in pairs.iter().enumerate() {
+        let pile = pile_tops.partition_point(|&top| pairs[top].1 < right_pos);
+        if pile > 0 {
+            back_links[idx] = Some(pile_tops[pile - 1]);
+        }
+        if pile == pile_tops.len() {
+            pile_tops.push(idx);
+        } else {
+            pile_tops[pile] = idx;
+        }
+    }
+
+    let mut chain = Vec::with_capacity(pile_tops.len());
+    let mut cursor = pile_tops.last().copied();
+    while let Some(idx) = cursor {
+        chain.push(pairs[idx]);
+        cursor = back_links[idx];
+    }
+    chain.reverse();
+    chain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_at_top_keeps_rest_equal() {
+        let old = vec!["b", "c", "d"];
+        let new = vec!["a", "b", "c", "d"];
+
+        let ops = diff(&old, &new);
+
+        assert_eq!(
+            ops,
+            vec![
+                DiffOp::Insert { old_index: 0, new_index: 0, new_len: 1 },
+                DiffOp::Equal { old_index: 0, new_index: 1, len: 3 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reordered_unique_lines() {
+        let old = vec!["x", "a", "y", "b", "z"];
+        let new = vec!["b", "x", "a", "z"];
+
+        let ops = diff(&old, &new);
+
+        let equal: usize = ops
+            .iter()
+            .map(|op| match op {
+                DiffOp::Equal { len, .. } => *len,
+                _ => 0,
+            })
+            .sum();
+        assert_eq!(equal, 3);
+    }
+}
@@ -0,0 +1,86 @@
+// 文本位置计算：字节偏移 → 字符偏移 / 行号 / 列号
+use super::TextRange;
+
+/// 只能向前推进的位置游标
+///
+/// 差异项按文本顺序生成，因此两侧各用一个游标即可在线性时间内算出所有位置。
+pub struct TextCursor<'a> {
+    text: &'a str,
+    byte: usize,
+    char: usize,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> TextCursor<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            byte: 0,
+            char: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    /// 推进到指定字节位置，返回 (字符偏移, 行号, 列号)
+    fn advance_to(&mut self, byte: usize) -> (usize, usize, usize) {
+        debug_assert!(byte >= self.byte, "TextCursor 只能向前推进");
+
+        if byte > self.byte {
+            for c in self.text[self.byte..byte].chars() {
+                self.char += 1;
+                if c == '\n' {
+                    self.line += 1;
+                    self.column = 1;
+                } else {
+                    self.column += 1;
+                }
+            }
+            self.byte = byte;
+        }
+
+        (self.char, self.line, self.column)
+    }
+
+    /// 计算字节区间 `[start, end)` 对应的完整位置信息
+    pub fn range(&mut self, start: usize, end: usize) -> TextRange {
+        let (char_start, start_line, start_column) = self.advance_to(start);
+        let (char_end, end_line, end_column) = self.advance_to(end);
+
+        TextRange {
+            byte_start: start,
+            byte_end: end,
+            char_start,
+            char_end,
+            start_line,
+            start_column,
+            end_line,
+            end_column,
+        }
+    }
+}
+
+impl TextRange {
+    /// 把相对于 `base` 起点计算的区间换算为全文区间
+    pub fn offset_by(&self, base: &TextRange) -> TextRange {
+        let column = |line: usize, column: usize| {
+            if line == 1 {
+                column + base.start_column - 1
+            } else {
+                column
+            }
+        };
+
+        TextRange {
+            byte_start: self.byte_start + base.byte_start,
+            byte_end: self.byte_end + base.byte_start,
+            char_start: self.char_start + base.char_start,
+            char_end: self.char_end + base.char_start,
+            start_line: self.start_line + base.start_line - 1,
+            start_column: column(self.start_line, self.start_column),
+            end_line: self.end_line + base.start_line - 1,
+            end_column: column(self.end_line, self.end_column),
+        }
+    }
+}
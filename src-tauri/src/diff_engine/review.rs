@@ -0,0 +1,293 @@
+// 逐项审阅：对每个非相等差异项接受或拒绝，生成合并后的文本
+use std::collections::HashMap;
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{DiffItem, DiffResult, DiffType};
+
+#[derive(Error, Debug)]
+pub enum ReviewError {
+    #[error("差异项不存在: {0}")]
+    UnknownItem(String),
+
+    #[error("相等项无需审阅: {0}")]
+    NotAChange(String),
+
+    #[error("只能审阅顶层差异项: {0}")]
+    NotTopLevel(String),
+}
+
+/// 审阅决定：接受即采用右侧的修改，拒绝或未决时保留左侧原文
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReviewDecision {
+    #[default]
+    Pending,
+    Accepted,
+    Rejected,
+}
+
+/// 审阅进度，按顶层的非相等差异项统计
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReviewProgress {
+    pub total: usize,
+    pub accepted: usize,
+    pub rejected: usize,
+    pub pending: usize,
+}
+
+/// 审阅会话：对比结果与按 `DiffItem.id` 记录的审阅决定，可序列化后保存以便继续审阅
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewSession {
+    pub left_text: String,
+    pub right_text: String,
+    pub result: DiffResult,
+    /// 顶层差异项的决定，未决的不记录
+    #[serde(default)]
+    pub decisions: HashMap<String, ReviewDecision>,
+}
+
+impl ReviewSession {
+    pub fn new(left_text: &str, right_text: &str, result: DiffResult) -> Self {
+        Self {
+            left_text: left_text.to_string(),
+            right_text: right_text.to_string(),
+            result,
+            decisions: HashMap::new(),
+        }
+    }
+
+    /// 记录某个顶层差异项的决定；合并时整项采用或保留，`inline_changes` 中的子项不能单独决定
+    pub fn decide(&mut self, item_id: &str, decision: ReviewDecision) -> Result<(), ReviewError> {
+        let Some(item) = self.result.items.iter().find(|item| item.id == item_id) else {
+            return Err(match find_item(&self.result.items, item_id) {
+                Some(_) => ReviewError::NotTopLevel(item_id.to_string()),
+                None => ReviewError::UnknownItem(item_id.to_string()),
+            });
+        };
+        if item.diff_type == DiffType::Equal {
+            return Err(ReviewError::NotAChange(item_id.to_string()));
+        }
+
+        if decision == ReviewDecision::Pending {
+            self.decisions.remove(item_id);
+        } else {
+            self.decisions.insert(item_id.to_string(), decision);
+        }
+        Ok(())
+    }
+
+    /// 对所有顶层差异项做同一决定
+    pub fn decide_all(&mut self, decision: ReviewDecision) {
+        self.decisions.clear();
+        if decision == ReviewDecision::Pending {
+            return;
+        }
+
+        let ids: Vec<String> = self.changes().map(|item| item.id.clone()).collect();
+        for id in ids {
+            self.decisions.insert(id, decision);
+        }
+    }
+
+    pub fn decision(&self, item_id: &str) -> ReviewDecision {
+        self.decisions.get(item_id).copied().unwrap_or_default()
+    }
+
+    /// 下一个尚未决定的顶层差异项
+    pub fn next_pending(&self) -> Option<&DiffItem> {
+        self.changes()
+            .find(|item| self.decision(&item.id) == ReviewDecision::Pending)
+    }
+
+    pub fn progress(&self) -> ReviewProgress {
+        let mut progress = ReviewProgress::default();
+
+        for item in self.changes() {
+            progress.total += 1;
+            match self.decision(&item.id) {
+                ReviewDecision::Pending => progress.pending += 1,
+                ReviewDecision::Accepted => progress.accepted += 1,
+                ReviewDecision::Rejected => progress.rejected += 1,
+            }
+        }
+
+        progress
+    }
+
+    /// 生成合并后的文本：以左侧原文为底，只应用已接受的修改
+    pub fn render(&self) -> String {
+        // 已接受的移动项在目标位置输出，来源处的原文随之略去
+        let mut moved_away: Vec<Range<usize>> = self
+            .result
+            .items
+            .iter()
+            .filter(|item| item.diff_type == DiffType::Move)
+            .filter(|item| self.decision(&item.id) == ReviewDecision::Accepted)
+            .map(|item| item.position.left.byte_start..item.position.left.byte_end)
+            .collect();
+        moved_away.sort_by_key(|range| range.start);
+
+        let mut merged = Merged {
+            session: self,
+            moved_away: &moved_away,
+            text: String::with_capacity(self.left_text.len().max(self.right_text.len())),
+        };
+        merged.render_items(&self.result.items);
+        merged.text
+    }
+
+    /// 可审阅的顶层差异项
+    fn changes(&self) -> impl Iterator<Item = &DiffItem> {
+        self.result
+            .items
+            .iter()
+            .filter(|item| item.diff_type != DiffType::Equal)
+    }
+}
+
+fn find_item<'a>(items: &'a [DiffItem], item_id: &str) -> Option<&'a DiffItem> {
+    items.iter().find_map(|item| {
+        if item.id == item_id {
+            Some(item)
+        } else {
+            find_item(&item.inline_changes, item_id)
+        }
+    })
+}
+
+struct Merged<'a> {
+    session: &'a ReviewSession,
+    moved_away: &'a [Range<usize>],
+    text: String,
+}
+
+impl Merged<'_> {
+    /// 按顺序输出差异项；差异项未覆盖的左侧原文（包括未接受的移动来源）原样保留
+    fn render_items(&mut self, items: &[DiffItem]) {
+        let mut cursor = 0;
+
+        for item in items {
+            let left = item.position.left.byte_start..item.position.left.byte_end;
+            let right = item.position.right.byte_start..item.position.right.byte_end;
+            let decision = self.session.decision(&item.id);
+
+            if item.diff_type == DiffType::Move {
+                if decision == ReviewDecision::Accepted {
+                    self.push_right(right);
+                }
+                continue;
+            }
+
+            self.push_left(cursor..left.start.max(cursor));
+            cursor = cursor.max(left.end);
+
+            match (item.diff_type, decision) {
+                (DiffType::Add, ReviewDecision::Accepted) | (DiffType::Modify, ReviewDecision::Accepted) => {
+                    self.push_right(right);
+                }
+                (DiffType::Add, _) | (DiffType::Remove, ReviewDecision::Accepted) => {}
+                _ => self.push_left(left),
+            }
+        }
+
+        self.push_left(cursor..self.session.left_text.len().max(cursor));
+    }
+
+    fn push_left(&mut self, range: Range<usize>) {
+        let mut start = range.start;
+
+        for moved in self.moved_away {
+            if moved.end <= start || moved.start >= range.end {
+                continue;
+            }
+            if moved.start > start {
+                self.text.push_str(&self.session.left_text[start..moved.start]);
+            }
+            start = start.max(moved.end);
+        }
+
+        if start < range.end {
+            self.text.push_str(&self.session.left_text[start..range.end]);
+        }
+    }
+
+    fn push_right(&mut self, range: Range<usize>) {
+        self.text.push_str(&self.session.right_text[range]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff_engine::{DiffEngine, DiffOptions};
+
+    fn session(left: &str, right: &str, options: DiffOptions) -> ReviewSession {
+        let result = DiffEngine::new(options).compute_diff(left, right);
+        ReviewSession::new(left, right, result)
+    }
+
+    #[test]
+    fn test_decisions_produce_merged_text() {
+        let options = DiffOptions {
+            split_by_word: true,
+            ..DiffOptions::default()
+        };
+        let mut review = session("the quick brown fox jumps", "the slow brown fox jumps high", options);
+        assert_eq!(review.render(), review.left_text);
+
+        review.decide_all(ReviewDecision::Accepted);
+        assert_eq!(review.render(), review.right_text);
+
+        review.decide_all(ReviewDecision::Pending);
+        let first = review.next_pending().unwrap().id.clone();
+        review.decide(&first, ReviewDecision::Accepted).unwrap();
+        assert_eq!(review.render(), "the slow brown fox jumps");
+
+        let progress = review.progress();
+        assert_eq!((progress.total, progress.accepted, progress.pending), (2, 1, 1));
+        assert!(matches!(review.decide("missing", ReviewDecision::Rejected), Err(ReviewError::UnknownItem(_))));
+    }
+
+    #[test]
+    fn test_moves_can_be_reviewed_and_nested_items_are_rejected() {
+        let options = DiffOptions {
+            split_by_paragraph: true,
+            ..DiffOptions::default()
+        };
+        let left = "甲方应在合同签订后十日内付款。\n\n乙方应按期交付全部货物。\n\n本合同一式两份。\n\n争议提交北京仲裁委员会仲裁。";
+        let right = "乙方应按期交付全部货物。\n\n甲方应在合同签订后十日内付款。\n\n本合同一式两份。\n\n争议提交上海仲裁委员会仲裁。";
+        let mut review = session(left, right, options);
+        assert!(review.result.items.iter().any(|item| item.diff_type == DiffType::Move));
+
+        review.decide_all(ReviewDecision::Accepted);
+        assert_eq!(review.render(), right);
+        review.decide_all(ReviewDecision::Rejected);
+        assert_eq!(review.render(), left);
+
+        // 修改项内部的子项不能单独决定
+        review.decide_all(ReviewDecision::Pending);
+        let modified = review
+            .result
+            .items
+            .iter()
+            .find(|item| item.diff_type == DiffType::Modify)
+            .unwrap();
+        let inline: Vec<String> = inline_ids(&modified.inline_changes);
+        assert!(!inline.is_empty());
+        for id in &inline {
+            assert!(matches!(review.decide(id, ReviewDecision::Accepted), Err(ReviewError::NotTopLevel(_))));
+        }
+        assert!(review.decisions.is_empty());
+        assert_eq!(review.render(), left);
+    }
+
+    fn inline_ids(items: &[DiffItem]) -> Vec<String> {
+        items
+            .iter()
+            .filter(|item| item.diff_type != DiffType::Equal)
+            .map(|item| item.id.clone())
+            .collect()
+    }
+}
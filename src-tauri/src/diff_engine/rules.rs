@@ -0,0 +1,280 @@
+// 用户自定义规则：忽略规则与等价规则，编译后供比较键归一化使用
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::control::DiffError;
+use super::DiffOptions;
+
+/// 等价规则：匹配的文本先改写为规范形式再比较，显示的仍是原文
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum EquivalenceRule {
+    /// 匹配正则的文本按替换模板改写，模板可用 `$1`、`${name}` 引用分组；
+    /// 正则须同时匹配各种写法，如 `¥\s*(\d+)|(\d+)\s*元` 改写为 `¥$1$2`
+    Regex { pattern: String, replacement: String },
+    /// 一组相互等价的词语，如 "Ltd." 与 "Limited"
+    Terms { terms: Vec<String> },
+    /// 中文数字（十二、二百零五、二〇二四）与阿拉伯数字（含全角）按数值比较
+    ChineseNumerals,
+}
+
+/// 可保存、分享的规则配置
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleProfile {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub ignore_patterns: Vec<String>,
+    #[serde(default)]
+    pub equivalence_rules: Vec<EquivalenceRule>,
+}
+
+impl RuleProfile {
+    /// 解析 JSON 配置并检查其中的正则
+    pub fn from_json(json: &str) -> Result<Self, DiffError> {
+        let profile: RuleProfile =
+            serde_json::from_str(json).map_err(|e| DiffError::InvalidProfile(e.to_string()))?;
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    /// 检查配置中的正则
+    pub fn validate(&self) -> Result<(), DiffError> {
+        self.apply_to(DiffOptions::default()).validate()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// 以配置中的规则替换对比选项中的规则
+    pub fn apply_to(&self, options: DiffOptions) -> DiffOptions {
+        DiffOptions {
+            ignore_patterns: self.ignore_patterns.clone(),
+            equivalence_rules: self.equivalence_rules.clone(),
+            ..options
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Canonicalizer {
+    Regex(Regex, String),
+    ChineseNumerals,
+}
+
+/// 编译好的规则
+#[derive(Debug, Clone, Default)]
+pub struct CompiledRules {
+    ignore: Vec<Regex>,
+    equivalences: Vec<Canonicalizer>,
+}
+
+impl CompiledRules {
+    /// 编译对比选项中的规则，任一正则无效时返回 `DiffError::InvalidPattern`
+    pub fn compile(options: &DiffOptions) -> Result<Self, DiffError> {
+        Ok(Self {
+            ignore: options
+                .ignore_patterns
+                .iter()
+                .map(|pattern| compile_pattern(pattern))
+                .collect::<Result<_, _>>()?,
+            equivalences: options
+                .equivalence_rules
+                .iter()
+                .filter_map(canonicalizer)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    /// 编译时跳过无效的规则
+    pub fn compile_lossy(options: &DiffOptions) -> Self {
+        Self {
+            ignore: options
+                .ignore_patterns
+                .iter()
+                .filter_map(|pattern| compile_pattern(pattern).ok())
+                .collect(),
+            equivalences: options
+                .equivalence_rules
+                .iter()
+                .filter_map(canonicalizer)
+                .filter_map(Result::ok)
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ignore.is_empty() && self.equivalences.is_empty()
+    }
+
+    /// 匹配忽略规则的区间
+    pub fn ignored(&self, text: &str) -> Vec<Range<usize>> {
+        self.ignore
+            .iter()
+            .flat_map(|pattern| pattern.find_iter(text))
+            .filter(|m| !m.is_empty())
+            .map(|m| m.range())
+            .collect()
+    }
+
+    /// 匹配等价规则的区间及其规范形式；各规则独立匹配原文
+    pub fn canonical(&self, text: &str) -> Vec<(Range<usize>, String)> {
+        let mut matches = Vec::new();
+
+        for canonicalizer in &self.equivalences {
+            match canonicalizer {
+                Canonicalizer::Regex(regex, replacement) => {
+                    for captures in regex.captures_iter(text) {
+                        let whole = captures.get(0).expect("分组 0 总是存在");
+                        if whole.is_empty() {
+                            continue;
+                        }
+                        let mut canonical = String::new();
+                        captures.expand(replacement, &mut canonical);
+                        matches.push((whole.range(), canonical));
+                    }
+                }
+                Canonicalizer::ChineseNumerals => {
+                    for numeral in chinese_numeral().find_iter(text) {
+                        if let Some(value) = parse_chinese_numeral(numeral.as_str()) {
+                            matches.push((numeral.range(), value));
+                        }
+                    }
+                }
+            }
+        }
+
+        matches
+    }
+}
+
+/// 编译单条等价规则；没有词语的词组规则不起作用
+fn canonicalizer(rule: &EquivalenceRule) -> Option<Result<Canonicalizer, DiffError>> {
+    Some(match rule {
+        EquivalenceRule::Regex { pattern, replacement } => {
+            compile_pattern(pattern).map(|regex| Canonicalizer::Regex(regex, replacement.clone()))
+        }
+        EquivalenceRule::Terms { terms } => {
+            // 第一个词为规范形式；长词优先匹配
+            let canonical = terms.first()?;
+            let mut alternatives: Vec<&String> = terms.iter().filter(|term| !term.is_empty()).collect();
+            alternatives.sort_by_key(|term| std::cmp::Reverse(term.len()));
+            let pattern = alternatives.iter().map(|term| regex::escape(term)).collect::<Vec<_>>().join("|");
+            compile_pattern(&pattern).map(|regex| Canonicalizer::Regex(regex, canonical.replace('$', "$$")))
+        }
+        EquivalenceRule::ChineseNumerals => Ok(Canonicalizer::ChineseNumerals),
+    })
+}
+
+fn compile_pattern(pattern: &str) -> Result<Regex, DiffError> {
+    Regex::new(pattern).map_err(|e| DiffError::InvalidPattern(format!("{}: {}", pattern, e)))
+}
+
+fn chinese_numeral() -> &'static Regex {
+    static NUMERAL: OnceLock<Regex> = OnceLock::new();
+    NUMERAL.get_or_init(|| Regex::new("[0-9０-９]+|[零〇一二两三四五六七八九十百千万亿]+").expect("数字正则无效"))
+}
+
+/// 中文数字 → 阿拉伯数字
+///
+/// 含十、百、千、万、亿等单位时按数值换算（十二 → 12，一万零五 → 10005），
+/// 否则逐位转写（二〇二四 → 2024，全角数字转为半角）；超出范围或不成数时返回 `None`。
+fn parse_chinese_numeral(numeral: &str) -> Option<String> {
+    let digit = |c: char| -> Option<u64> {
+        Some(match c {
+            '零' | '〇' => 0,
+            '一' => 1,
+            '二' | '两' => 2,
+            '三' => 3,
+            '四' => 4,
+            '五' => 5,
+            '六' => 6,
+            '七' => 7,
+            '八' => 8,
+            '九' => 9,
+            '0'..='9' => c as u64 - '0' as u64,
+            '０'..='９' => c as u64 - '０' as u64,
+            _ => return None,
+        })
+    };
+
+    if numeral.chars().all(|c| digit(c).is_some()) {
+        return numeral.chars().map(|c| digit(c).map(|d| char::from(b'0' + d as u8))).collect();
+    }
+
+    let (mut total, mut section, mut number) = (0u64, 0u64, 0u64);
+    for c in numeral.chars() {
+        if let Some(d) = digit(c) {
+            number = d;
+            continue;
+        }
+        match c {
+            '十' | '百' | '千' => {
+                let unit = match c {
+                    '十' => 10,
+                    '百' => 100,
+                    _ => 1000,
+                };
+                // "十二" 省略了开头的 "一"
+                section = section.checked_add(number.max(1).checked_mul(unit)?)?;
+                number = 0;
+            }
+            '万' | '亿' if section + number == 0 => return None,
+            '万' => {
+                total = total.checked_add(section.checked_add(number)?.checked_mul(10_000)?)?;
+                (section, number) = (0, 0);
+            }
+            '亿' => {
+                total = total.checked_add(section.checked_add(number)?)?.checked_mul(100_000_000)?;
+                (section, number) = (0, 0);
+            }
+            _ => return None,
+        }
+    }
+
+    Some(total.checked_add(section)?.checked_add(number)?.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chinese_numerals() {
+        let parse = |numeral| parse_chinese_numeral(numeral).unwrap();
+        assert_eq!(parse("一"), "1");
+        assert_eq!(parse("十二"), "12");
+        assert_eq!(parse("二十"), "20");
+        assert_eq!(parse("二百零五"), "205");
+        assert_eq!(parse("一千零一十"), "1010");
+        assert_eq!(parse("三万五千"), "35000");
+        assert_eq!(parse("一亿零二万"), "100020000");
+        assert_eq!(parse("二〇二四"), "2024");
+        assert_eq!(parse("两"), "2");
+        assert_eq!(parse("１２"), "12");
+        assert_eq!(parse_chinese_numeral("万"), None);
+    }
+
+    #[test]
+    fn test_profile_round_trip_and_validation() {
+        let profile = RuleProfile {
+            name: "合同".to_string(),
+            description: String::new(),
+            ignore_patterns: vec![r"\d{4}-\d{2}-\d{2}".to_string()],
+            equivalence_rules: vec![
+                EquivalenceRule::Terms { terms: vec!["Limited".to_string(), "Ltd.".to_string()] },
+                EquivalenceRule::ChineseNumerals,
+            ],
+        };
+        assert_eq!(RuleProfile::from_json(&profile.to_json()).unwrap(), profile);
+
+        let json = r#"{"name": "x", "equivalence_rules": [{"kind": "regex", "pattern": "(", "replacement": ""}]}"#;
+        assert!(matches!(RuleProfile::from_json(json), Err(DiffError::InvalidPattern(_))));
+        assert!(matches!(RuleProfile::from_json("{"), Err(DiffError::InvalidProfile(_))));
+    }
+}
@@ -0,0 +1,247 @@
+// 并排视图：把任意粒度的对比结果按行对齐为左右两栏，供双栏界面与并排导出直接渲染
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use super::patch::{align_lines, context_pairs, Line};
+use super::{DiffItem, DiffMetadata, DiffStats, DiffType};
+
+/// 行内高亮：单元格文本中的字符区间（左闭右开）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Highlight {
+    pub start: usize,
+    pub end: usize,
+    pub diff_type: DiffType,
+}
+
+/// 一侧的单元格：行号从1开始，文本不含换行符
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RowCell {
+    pub line: usize,
+    pub text: String,
+    pub highlights: Vec<Highlight>,
+}
+
+/// 并排视图中的一行；某侧为空时该侧渲染为填充行
+///
+/// 两侧原样相同的行为 `Equal`；两侧都有内容的改动行为 `Modify`（只涉及移动时为 `Move`），
+/// 只有一侧的行为 `Remove` 或 `Add`。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlignedRow {
+    pub kind: DiffType,
+    pub left: Option<RowCell>,
+    pub right: Option<RowCell>,
+}
+
+/// 并排视图的对比结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SideBySide {
+    pub rows: Vec<AlignedRow>,
+    pub stats: DiffStats,
+    pub metadata: DiffMetadata,
+}
+
+/// 按行对齐对比结果
+///
+/// 与统一格式补丁相同，整行原样对应的行作为相等行；两个相等行之间的其余行按顺序两两配对，
+/// 多出的行另一侧填充空行。行内高亮取自最细一层的子差异。
+pub fn align_rows(items: &[DiffItem], left: &str, right: &str) -> Vec<AlignedRow> {
+    let left_lines: Vec<&str> = left.split_inclusive('\n').collect();
+    let right_lines: Vec<&str> = right.split_inclusive('\n').collect();
+    let left_side = Side::new(&left_lines, changed_ranges(items, true));
+    let right_side = Side::new(&right_lines, changed_ranges(items, false));
+
+    let lines = align_lines(&context_pairs(items, &left_lines, &right_lines), left_lines.len(), right_lines.len());
+    let mut rows = Vec::with_capacity(lines.len());
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+
+    for line in lines.iter().copied().chain([Line::Context(usize::MAX, usize::MAX)]) {
+        match line {
+            Line::Removed(i) => removed.push(i),
+            Line::Added(j) => added.push(j),
+            Line::Context(i, j) => {
+                for k in 0..removed.len().max(added.len()) {
+                    let left_cell = removed.get(k).map(|&i| left_side.cell(i));
+                    let right_cell = added.get(k).map(|&j| right_side.cell(j));
+                    rows.push(changed_row(left_cell, right_cell));
+                }
+                removed.clear();
+                added.clear();
+
+                if i != usize::MAX {
+                    rows.push(AlignedRow {
+                        kind: DiffType::Equal,
+                        left: Some(left_side.cell(i)),
+                        right: Some(right_side.cell(j)),
+                    });
+                }
+            }
+        }
+    }
+
+    rows
+}
+
+/// 由差异项还原两侧原文
+pub fn source_texts(items: &[DiffItem]) -> (String, String) {
+    let mut left: Vec<(usize, &str)> = Vec::new();
+    let mut right: Vec<(usize, &str)> = Vec::new();
+
+    for item in items {
+        let original = item.original_content.as_deref().unwrap_or(&item.content);
+        if item.diff_type != DiffType::Add {
+            left.push((item.position.left.byte_start, original));
+        }
+        if item.diff_type != DiffType::Remove {
+            right.push((item.position.right.byte_start, &item.content));
+        }
+    }
+
+    // 移动项在左侧位于来源位置，需按位置重新排序
+    left.sort_by_key(|&(start, _)| start);
+    right.sort_by_key(|&(start, _)| start);
+    (
+        left.into_iter().map(|(_, text)| text).collect(),
+        right.into_iter().map(|(_, text)| text).collect(),
+    )
+}
+
+fn changed_row(left: Option<RowCell>, right: Option<RowCell>) -> AlignedRow {
+    let highlights: Vec<DiffType> = left
+        .iter()
+        .chain(right.iter())
+        .flat_map(|cell| cell.highlights.iter().map(|highlight| highlight.diff_type))
+        .collect();
+
+    let kind = if !highlights.is_empty() && highlights.iter().all(|&diff_type| diff_type == DiffType::Move) {
+        DiffType::Move
+    } else {
+        match (&left, &right) {
+            (Some(_), None) => DiffType::Remove,
+            (None, _) => DiffType::Add,
+            // 忽略选项下两侧不同但视为相同的行
+            (Some(_), Some(_)) if highlights.is_empty() => DiffType::Equal,
+            (Some(_), Some(_)) => DiffType::Modify,
+        }
+    };
+
+    AlignedRow { kind, left, right }
+}
+
+/// 最细一层子差异在一侧原文中的改动区间，按起点排序
+fn changed_ranges(items: &[DiffItem], left: bool) -> Vec<(Range<usize>, DiffType)> {
+    fn collect(items: &[DiffItem], left: bool, out: &mut Vec<(Range<usize>, DiffType)>) {
+        for item in items {
+            if !item.inline_changes.is_empty() {
+                collect(&item.inline_changes, left, out);
+                continue;
+            }
+
+            let on_this_side = match item.diff_type {
+                DiffType::Equal => false,
+                DiffType::Add => !left,
+                DiffType::Remove => left,
+                DiffType::Modify | DiffType::Move => true,
+            };
+            let range = if left { &item.position.left } else { &item.position.right };
+            if on_this_side && range.byte_start < range.byte_end {
+                out.push((range.byte_start..range.byte_end, item.diff_type));
+            }
+        }
+    }
+
+    let mut ranges = Vec::new();
+    collect(items, left, &mut ranges);
+    ranges.sort_by_key(|(range, _)| range.start);
+    ranges
+}
+
+/// 一侧的行与改动区间
+struct Side<'a> {
+    lines: &'a [&'a str],
+    starts: Vec<usize>,
+    changes: Vec<(Range<usize>, DiffType)>,
+    /// 改动区间终点的前缀最大值，用于跳过已结束的区间
+    reach: Vec<usize>,
+}
+
+impl<'a> Side<'a> {
+    fn new(lines: &'a [&'a str], changes: Vec<(Range<usize>, DiffType)>) -> Self {
+        let starts = lines
+            .iter()
+            .scan(0, |offset, line| {
+                let start = *offset;
+                *offset += line.len();
+                Some(start)
+            })
+            .collect();
+        let reach = changes
+            .iter()
+            .scan(0, |reach, (range, _)| {
+                *reach = range.end.max(*reach);
+                Some(*reach)
+            })
+            .collect();
+        Self { lines, starts, changes, reach }
+    }
+
+    fn cell(&self, index: usize) -> RowCell {
+        let line = self.lines[index];
+        let text = line.trim_end_matches('\n').trim_end_matches('\r');
+        let start = self.starts[index];
+        let end = start + text.len();
+        let char_offset = |byte: usize| text[..byte - start].chars().count();
+
+        let first = self.reach.partition_point(|&reach| reach <= start);
+        let highlights = self.changes[first..]
+            .iter()
+            .take_while(|(range, _)| range.start < end)
+            .filter(|(range, _)| range.end > start)
+            .filter_map(|(range, diff_type)| {
+                let (from, to) = (range.start.max(start), range.end.min(end));
+                (from < to).then(|| Highlight {
+                    start: char_offset(from),
+                    end: char_offset(to),
+                    diff_type: *diff_type,
+                })
+            })
+            .collect();
+
+        RowCell {
+            line: index + 1,
+            text: text.to_string(),
+            highlights,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff_engine::{DiffEngine, DiffOptions};
+
+    #[test]
+    fn test_rows_pair_changes_and_fill_gaps() {
+        let left = "第一行\n第二行\n第三行\n";
+        let right = "第一行\n第二行修改了\n新增一行\n第三行\n";
+
+        let engine = DiffEngine::new(DiffOptions::default());
+        let result = engine.compute_diff(left, right);
+        let rows = align_rows(&result.items, left, right);
+
+        let kinds: Vec<_> = rows.iter().map(|row| row.kind).collect();
+        assert_eq!(kinds, vec![DiffType::Equal, DiffType::Modify, DiffType::Add, DiffType::Equal]);
+
+        let modified = &rows[1];
+        let right_cell = modified.right.as_ref().unwrap();
+        assert_eq!(right_cell.text, "第二行修改了");
+        assert_eq!(right_cell.highlights, vec![Highlight { start: 3, end: 6, diff_type: DiffType::Add }]);
+        assert!(modified.left.as_ref().unwrap().highlights.is_empty());
+
+        // 新增行左侧为填充行，行号对应各自一侧
+        assert!(rows[2].left.is_none());
+        assert_eq!((rows[3].left.as_ref().unwrap().line, rows[3].right.as_ref().unwrap().line), (3, 4));
+
+        assert_eq!(source_texts(&result.items), (left.to_string(), right.to_string()));
+    }
+}
@@ -0,0 +1,216 @@
+// 对比统计：变化计数、字词数与按字符计算的相似度指标
+use std::collections::HashSet;
+
+use super::tokenizer::{is_cjk_ideograph, Tokenizer};
+use super::{DiffItem, DiffStats, DiffType, TextRange};
+
+/// Jaccard 相似度所用词组（shingle）的词数
+const SHINGLE_SIZE: usize = 2;
+
+/// 可分批累加的统计，各批差异项须处于同一坐标系（流式对比中为全文坐标）
+///
+/// 字词数、编辑距离与变化行数按最细一层子差异计算，修改项只计入实际变化的部分；
+/// 相邻的删除与新增视为替换，编辑距离取两者字符数的较大值。
+#[derive(Default)]
+pub struct StatsBuilder {
+    stats: DiffStats,
+    matched_chars: usize,
+    run_deleted: usize,
+    run_inserted: usize,
+    left_lines: HashSet<usize>,
+    right_lines: HashSet<usize>,
+}
+
+impl StatsBuilder {
+    pub fn add_items(&mut self, items: &[DiffItem]) {
+        for item in items {
+            match item.diff_type {
+                DiffType::Add => self.stats.additions += 1,
+                DiffType::Remove => self.stats.deletions += 1,
+                DiffType::Modify => self.stats.modifications += 1,
+                DiffType::Move => self.stats.moves += 1,
+                DiffType::Equal => {}
+            }
+            self.add_leaf(item);
+        }
+    }
+
+    fn add_leaf(&mut self, item: &DiffItem) {
+        if !item.inline_changes.is_empty() {
+            for child in &item.inline_changes {
+                self.add_leaf(child);
+            }
+            return;
+        }
+
+        let left_chars = item.position.left.char_end - item.position.left.char_start;
+        let right_chars = item.position.right.char_end - item.position.right.char_start;
+        let original = item.original_content.as_deref().unwrap_or_default();
+
+        match item.diff_type {
+            DiffType::Equal => self.add_matched(left_chars + right_chars),
+            // 内容未变的移动只是换了位置
+            DiffType::Move if original == item.content => self.add_matched(left_chars + right_chars),
+            DiffType::Add => {
+                self.count_added(&item.content, right_chars);
+                mark_lines(&mut self.right_lines, &item.position.right);
+            }
+            DiffType::Remove => {
+                self.count_deleted(&item.content, left_chars);
+                mark_lines(&mut self.left_lines, &item.position.left);
+            }
+            DiffType::Modify | DiffType::Move => {
+                self.count_added(&item.content, right_chars);
+                self.count_deleted(original, left_chars);
+                mark_lines(&mut self.left_lines, &item.position.left);
+                mark_lines(&mut self.right_lines, &item.position.right);
+            }
+        }
+    }
+
+    /// 相同内容结束一段连续的变化
+    fn add_matched(&mut self, chars: usize) {
+        self.matched_chars += chars;
+        self.stats.edit_distance = self.edit_distance();
+        self.run_deleted = 0;
+        self.run_inserted = 0;
+    }
+
+    fn count_added(&mut self, text: &str, chars: usize) {
+        self.stats.added_words += count_words(text);
+        self.stats.added_chars += text.chars().filter(|c| !c.is_whitespace()).count();
+        self.run_inserted += chars;
+    }
+
+    fn count_deleted(&mut self, text: &str, chars: usize) {
+        self.stats.deleted_words += count_words(text);
+        self.stats.deleted_chars += text.chars().filter(|c| !c.is_whitespace()).count();
+        self.run_deleted += chars;
+    }
+
+    fn edit_distance(&self) -> usize {
+        self.stats.edit_distance + self.run_deleted.max(self.run_inserted)
+    }
+
+    /// 按两侧全文计算相似度指标，得到最终统计
+    pub fn finish(&self, left_text: &str, right_text: &str) -> DiffStats {
+        let left_chars = left_text.chars().count();
+        let right_chars = right_text.chars().count();
+        let longer = left_chars.max(right_chars);
+
+        let mut stats = self.stats.clone();
+        stats.total_changes = stats.additions + stats.deletions + stats.modifications + stats.moves;
+        stats.edit_distance = self.edit_distance();
+        stats.left_changed_lines = self.left_lines.len();
+        stats.right_changed_lines = self.right_lines.len();
+        stats.similarity = percentage(self.matched_chars, left_chars + right_chars);
+        stats.levenshtein_similarity = percentage(longer.saturating_sub(stats.edit_distance), longer);
+        stats.jaccard_similarity = jaccard(left_text, right_text);
+        stats
+    }
+}
+
+/// 字词数：每个汉字计一个，其余按词元计，标点与空白不计
+pub fn count_words(text: &str) -> usize {
+    Tokenizer::builtin()
+        .tokenize(text)
+        .into_iter()
+        .map(|token| {
+            let token = &text[token];
+            let ideographs = token.chars().filter(|&c| is_cjk_ideograph(c)).count();
+            if ideographs > 0 {
+                ideographs
+            } else {
+                usize::from(token.chars().any(char::is_alphanumeric))
+            }
+        })
+        .sum()
+}
+
+/// 两侧按词组集合计算的 Jaccard 相似度（百分比）
+fn jaccard(left_text: &str, right_text: &str) -> f32 {
+    let left_words = words(left_text);
+    let right_words = words(right_text);
+    let left = shingles(&left_words);
+    let right = shingles(&right_words);
+
+    let union = left.union(&right).count();
+    percentage(left.intersection(&right).count(), union)
+}
+
+fn words(text: &str) -> Vec<&str> {
+    Tokenizer::builtin()
+        .tokenize(text)
+        .into_iter()
+        .map(|token| &text[token])
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .collect()
+}
+
+/// 相邻词组成的词组集合，词数不足时整体作为一个词组
+fn shingles<'a, 'b>(words: &'b [&'a str]) -> HashSet<&'b [&'a str]> {
+    if words.len() < SHINGLE_SIZE {
+        return words.chunks(SHINGLE_SIZE).collect();
+    }
+    words.windows(SHINGLE_SIZE).collect()
+}
+
+/// 区间涉及的行；止于行首的区间不计末行
+fn mark_lines(lines: &mut HashSet<usize>, range: &TextRange) {
+    if range.byte_start == range.byte_end {
+        return;
+    }
+    let end_line = if range.end_column == 1 && range.end_line > range.start_line {
+        range.end_line - 1
+    } else {
+        range.end_line
+    };
+    lines.extend(range.start_line..=end_line);
+}
+
+/// 百分比，分母为零时视为完全相同
+pub fn percentage(part: usize, whole: usize) -> f32 {
+    if whole == 0 {
+        return 100.0;
+    }
+    (part as f32 / whole as f32 * 100.0).clamp(0.0, 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff_engine::{DiffEngine, DiffOptions};
+
+    #[test]
+    fn test_similarity_counts_chars_and_modify_details() {
+        // 按字节计算时一个汉字算三个单位，相似度会被严重低估
+        let engine = DiffEngine::new(DiffOptions::default());
+        let result = engine.compute_diff("今天天气很好，我们去公园散步。", "今天天气不错，我们去公园散步。");
+        let stats = &result.stats;
+
+        // 15 个字中 13 个相同
+        assert!((stats.similarity - 13.0 * 2.0 / 30.0 * 100.0).abs() < 0.01);
+        assert_eq!(stats.edit_distance, 2);
+        assert!((stats.levenshtein_similarity - 13.0 / 15.0 * 100.0).abs() < 0.01);
+        assert_eq!((stats.added_chars, stats.deleted_chars), (2, 2));
+        assert_eq!((stats.added_words, stats.deleted_words), (2, 2));
+        assert_eq!((stats.left_changed_lines, stats.right_changed_lines), (1, 1));
+        assert!(stats.jaccard_similarity > 0.0 && stats.jaccard_similarity < 100.0);
+    }
+
+    #[test]
+    fn test_word_counts_and_identical_texts() {
+        assert_eq!(count_words("Hello, world! 你好世界"), 6);
+        assert_eq!(count_words("  ，。 "), 0);
+
+        let engine = DiffEngine::new(DiffOptions::default());
+        let stats = engine.compute_diff("第一行\n第二行\n", "第一行\n第二行\n").stats;
+        assert_eq!(stats.similarity, 100.0);
+        assert_eq!(stats.levenshtein_similarity, 100.0);
+        assert_eq!(stats.jaccard_similarity, 100.0);
+        assert_eq!(stats.total_changes, 0);
+
+        let stats = engine.compute_diff("", "").stats;
+        assert_eq!(stats.similarity, 100.0);
+    }
+}
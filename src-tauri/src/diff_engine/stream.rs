@@ -0,0 +1,117 @@
+// 流式对比：以两侧各只出现一次的比较单元为锚点切块，块与块之间保持对齐，逐块输出差异
+use std::vec;
+
+use super::partition::{plan_regions, Region, Stitcher};
+use super::stats::StatsBuilder;
+use super::{DiffChunk, DiffEngine, DiffStats};
+
+/// 逐块产生差异的迭代器
+///
+/// 每块内部按对比选项完整对比（移动检测限于块内），差异项的位置与编号换算为全文坐标；
+/// 遍历结束后 `stats` 即为整体统计；引擎被取消后不再产生新的块。
+pub struct DiffStream<'a> {
+    engine: &'a DiffEngine,
+    left: &'a str,
+    right: &'a str,
+    chunks: vec::IntoIter<Region>,
+    total: usize,
+    index: usize,
+    has_chinese: bool,
+    stitcher: Stitcher<'a>,
+    stats: StatsBuilder,
+}
+
+impl<'a> DiffStream<'a> {
+    pub(super) fn new(engine: &'a DiffEngine, left: &'a str, right: &'a str, chunk_units: usize) -> Self {
+        let chunks = plan_regions(engine, left, right, chunk_units);
+
+        Self {
+            engine,
+            left,
+            right,
+            total: chunks.len(),
+            chunks: chunks.into_iter(),
+            index: 0,
+            has_chinese: engine.contains_chinese(left) || engine.contains_chinese(right),
+            stitcher: Stitcher::new(left, right),
+            stats: StatsBuilder::default(),
+        }
+    }
+
+    /// 已输出各块的累计统计，相似度按全文计算
+    pub fn stats(&self) -> DiffStats {
+        self.stats.finish(self.left, self.right)
+    }
+}
+
+impl Iterator for DiffStream<'_> {
+    type Item = DiffChunk;
+
+    fn next(&mut self) -> Option<DiffChunk> {
+        if self.engine.is_cancelled() {
+            return None;
+        }
+
+        let region = self.chunks.next()?;
+        let result = self.engine.diff_part(
+            &self.left[region.left.clone()],
+            &self.right[region.right.clone()],
+            self.has_chinese,
+        );
+
+        let mut items = Vec::with_capacity(result.items.len());
+        self.stitcher.place(&region, result.items, &mut items);
+        self.stats.add_items(&items);
+
+        let chunk = DiffChunk {
+            index: self.index,
+            total: self.total,
+            items,
+            partial_stats: result.stats,
+        };
+        self.index += 1;
+        Some(chunk)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diff_engine::{DiffEngine, DiffOptions, DiffType};
+
+    #[test]
+    fn test_inserted_line_does_not_misalign_later_chunks() {
+        let left: String = (0..40).map(|i| format!("第{}行内容\n", i)).collect();
+        let right = format!("新增的一行\n{}", left.replacen("第30行内容", "第30行修改", 1));
+
+        let engine = DiffEngine::new(DiffOptions::default());
+        let mut stream = engine.diff_chunks(&left, &right, 10);
+        let chunks: Vec<_> = stream.by_ref().collect();
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|chunk| chunk.total == chunks.len()));
+
+        let items: Vec<_> = chunks.iter().flat_map(|chunk| &chunk.items).collect();
+        let whole = engine.compute_diff(&left, &right);
+        let changes = |diff_type| items.iter().filter(|item| item.diff_type == diff_type).count();
+        assert_eq!(changes(DiffType::Add), whole.stats.additions);
+        assert_eq!(changes(DiffType::Modify), whole.stats.modifications);
+
+        // 各项位置为全文坐标，编号连续
+        for (index, item) in items.iter().enumerate() {
+            assert_eq!(item.id, format!("diff-{}", index));
+            let (text, range) = match item.diff_type {
+                DiffType::Remove => (&left, &item.position.left),
+                _ => (&right, &item.position.right),
+            };
+            assert_eq!(&text[range.byte_start..range.byte_end], item.content);
+        }
+
+        let stats = stream.stats();
+        assert_eq!(stats.total_changes, whole.stats.total_changes);
+        assert_eq!(stats.similarity, whole.stats.similarity);
+        assert_eq!(stats.right_changed_lines, whole.stats.right_changed_lines);
+    }
+}
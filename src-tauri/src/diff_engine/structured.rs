@@ -0,0 +1,384 @@
+// 结构化对比：JSON、YAML、TOML 解析为树后按路径对比，差异项带 JSON Pointer 路径
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::algorithm::{intern, SequenceDiff};
+use super::control::DiffError;
+use super::normalize::Normalizer;
+use super::ops::pair_elements;
+use super::stats::{percentage, StatsBuilder};
+use super::{DiffItem, DiffStats, DiffType, Position};
+
+/// 数组元素默认的配对字段
+const DEFAULT_ID_KEY: &str = "id";
+
+/// 结构化文档格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StructuredFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl StructuredFormat {
+    /// 解析为 JSON 值；TOML 的日期时间转为字符串
+    pub fn parse(self, text: &str) -> Result<Value, DiffError> {
+        let invalid = |e: &dyn std::fmt::Display| DiffError::InvalidDocument(format!("{:?}: {}", self, e));
+        match self {
+            StructuredFormat::Json => serde_json::from_str(text).map_err(|e| invalid(&e)),
+            StructuredFormat::Yaml => serde_yaml::from_str(text).map_err(|e| invalid(&e)),
+            StructuredFormat::Toml => text
+                .parse::<toml::Table>()
+                .map(|table| toml_to_json(toml::Value::Table(table)))
+                .map_err(|e| invalid(&e)),
+        }
+    }
+}
+
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        // NaN 与无穷大没有对应的 JSON 数字
+        toml::Value::Float(f) => serde_json::Number::from_f64(f).map_or_else(|| Value::String(f.to_string()), Value::Number),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(datetime) => Value::String(datetime.to_string()),
+        toml::Value::Array(array) => Value::Array(array.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => Value::Object(table.into_iter().map(|(key, value)| (key, toml_to_json(value))).collect()),
+    }
+}
+
+/// 按路径对比两棵树
+///
+/// 对象按键对比，键的顺序不影响结果；数组中的对象都带有唯一的配对字段时按该字段配对（顺序不计），
+/// 否则按元素序列对比，相邻的删除与新增逐个配对后递归对比。
+/// 忽略选项与等价规则作用于字符串值、对象的键与配对字段的值。
+/// 差异项的 `content` 为右侧值（删除项为左侧值）的紧凑 JSON，`original_content` 为左侧值，
+/// `path` 为 JSON Pointer（删除项取左侧路径，其余取右侧路径），`position` 为空。
+pub struct TreeDiff<'a> {
+    pub normalizer: Normalizer<'a>,
+    pub algorithm: &'static dyn SequenceDiff,
+    pub id_key: Option<&'a str>,
+}
+
+impl TreeDiff<'_> {
+    pub fn diff(&self, left: &Value, right: &Value) -> (Vec<DiffItem>, DiffStats) {
+        let mut output = Output::default();
+        self.diff_value(&mut String::new(), left, right, &mut output);
+        let stats = output.stats(left, right);
+        (output.items, stats)
+    }
+
+    fn diff_value(&self, path: &mut String, left: &Value, right: &Value, out: &mut Output) {
+        if self.same(left, right) {
+            out.equal_leaves += leaves(right).len();
+            out.push(path, DiffType::Equal, Some(left), Some(right));
+            return;
+        }
+
+        match (left, right) {
+            (Value::Object(left_map), Value::Object(right_map)) => self.diff_objects(path, left_map, right_map, out),
+            (Value::Array(left_array), Value::Array(right_array)) => {
+                let id_key = self.id_key.unwrap_or(DEFAULT_ID_KEY);
+                let ids = |array| element_ids(array, id_key, &self.normalizer);
+                match (ids(left_array), ids(right_array)) {
+                    (Some(left_ids), Some(right_ids)) => {
+                        self.diff_by_id(path, left_array, &left_ids, right_array, &right_ids, out)
+                    }
+                    _ => self.diff_sequence(path, left_array, right_array, out),
+                }
+            }
+            _ => out.push(path, DiffType::Modify, Some(left), Some(right)),
+        }
+    }
+
+    /// 键按比较键配对；删除项取左侧的键，其余取右侧的键
+    fn diff_objects(&self, path: &mut String, left: &Map<String, Value>, right: &Map<String, Value>, out: &mut Output) {
+        let (left_keys, right_keys) = (self.object_keys(left), self.object_keys(right));
+
+        for ((key, left_value), compare_key) in left.iter().zip(&left_keys) {
+            if !right_keys.contains(compare_key) {
+                with_segment(path, key, |path| out.push(path, DiffType::Remove, Some(left_value), None));
+            }
+        }
+
+        let left_index: HashMap<&str, &Value> = left_keys.iter().map(Cow::as_ref).zip(left.values()).collect();
+        for ((key, right_value), compare_key) in right.iter().zip(&right_keys) {
+            with_segment(path, key, |path| match left_index.get(compare_key.as_ref()) {
+                Some(left_value) => self.diff_value(path, left_value, right_value, out),
+                None => out.push(path, DiffType::Add, None, Some(right_value)),
+            });
+        }
+    }
+
+    /// 对象各键的比较键，按对象中键的顺序排列；规范化后有重复的键时按原样比较
+    fn object_keys<'v>(&self, map: &'v Map<String, Value>) -> Vec<Cow<'v, str>> {
+        let keys: Vec<Cow<str>> = map.keys().map(|key| self.normalizer.key(key)).collect();
+        let distinct: HashSet<&str> = keys.iter().map(Cow::as_ref).collect();
+        if distinct.len() < keys.len() {
+            return map.keys().map(|key| Cow::Borrowed(key.as_str())).collect();
+        }
+        keys
+    }
+
+    fn diff_by_id(
+        &self,
+        path: &mut String,
+        left: &[Value],
+        left_ids: &[String],
+        right: &[Value],
+        right_ids: &[String],
+        out: &mut Output,
+    ) {
+        let right_index: HashMap<&str, usize> = right_ids.iter().enumerate().map(|(j, id)| (id.as_str(), j)).collect();
+        let left_index: HashMap<&str, usize> = left_ids.iter().enumerate().map(|(i, id)| (id.as_str(), i)).collect();
+
+        for (i, id) in left_ids.iter().enumerate() {
+            if !right_index.contains_key(id.as_str()) {
+                with_segment(path, &i.to_string(), |path| out.push(path, DiffType::Remove, Some(&left[i]), None));
+            }
+        }
+        for (j, id) in right_ids.iter().enumerate() {
+            with_segment(path, &j.to_string(), |path| match left_index.get(id.as_str()) {
+                Some(&i) => self.diff_value(path, &left[i], &right[j], out),
+                None => out.push(path, DiffType::Add, None, Some(&right[j])),
+            });
+        }
+    }
+
+    fn diff_sequence(&self, path: &mut String, left: &[Value], right: &[Value], out: &mut Output) {
+        let left_keys: Vec<String> = left.iter().map(|value| self.compare_key(value)).collect();
+        let right_keys: Vec<String> = right.iter().map(|value| self.compare_key(value)).collect();
+        let (left_ids, right_ids) = intern(
+            &left_keys.iter().map(String::as_str).collect::<Vec<_>>(),
+            &right_keys.iter().map(String::as_str).collect::<Vec<_>>(),
+        );
+
+        // 相邻的删除与新增逐个配对后递归对比；删除项取左侧下标，其余取右侧下标
+        for pair in pair_elements(&self.algorithm.diff(&left_ids, &right_ids)) {
+            match pair {
+                (Some(i), Some(j)) => with_segment(path, &j.to_string(), |path| self.diff_value(path, &left[i], &right[j], out)),
+                (Some(i), None) => with_segment(path, &i.to_string(), |path| out.push(path, DiffType::Remove, Some(&left[i]), None)),
+                (None, Some(j)) => with_segment(path, &j.to_string(), |path| out.push(path, DiffType::Add, None, Some(&right[j]))),
+                (None, None) => {}
+            }
+        }
+    }
+
+    /// 忽略选项与等价规则作用于字符串值与对象的键
+    fn same(&self, left: &Value, right: &Value) -> bool {
+        match (left, right) {
+            (Value::String(a), Value::String(b)) => self.normalizer.key(a) == self.normalizer.key(b),
+            (Value::Array(a), Value::Array(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| self.same(a, b)),
+            (Value::Object(a), Value::Object(b)) => {
+                let b_index: HashMap<Cow<str>, &Value> = self.object_keys(b).into_iter().zip(b.values()).collect();
+                a.len() == b.len()
+                    && self
+                        .object_keys(a)
+                        .iter()
+                        .zip(a.values())
+                        .all(|(key, a)| b_index.get(key).is_some_and(|b| self.same(a, b)))
+            }
+            _ => left == right,
+        }
+    }
+
+    fn compare_key(&self, value: &Value) -> String {
+        if self.normalizer.is_identity() {
+            return value.to_string();
+        }
+        normalized(value, &self.normalizer).to_string()
+    }
+}
+
+fn normalized(value: &Value, normalizer: &Normalizer) -> Value {
+    match value {
+        Value::String(s) => Value::String(normalizer.key(s).into_owned()),
+        Value::Array(array) => Value::Array(array.iter().map(|value| normalized(value, normalizer)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (normalizer.key(key).into_owned(), normalized(value, normalizer)))
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
+
+/// 数组元素的配对值（字符串取比较键）；任一元素不是带配对字段的对象或配对值重复时返回 `None`
+fn element_ids(array: &[Value], id_key: &str, normalizer: &Normalizer) -> Option<Vec<String>> {
+    let mut seen = HashSet::new();
+    array
+        .iter()
+        .map(|element| {
+            let id = match element.get(id_key)? {
+                Value::String(s) => normalizer.key(s).into_owned(),
+                id @ (Value::Number(_) | Value::Bool(_)) => id.to_string(),
+                _ => return None,
+            };
+            seen.insert(id.clone()).then_some(id)
+        })
+        .collect()
+}
+
+/// 在路径末尾临时追加一段，按 JSON Pointer 规则转义 `~` 与 `/`
+fn with_segment<R>(path: &mut String, segment: &str, f: impl FnOnce(&mut String) -> R) -> R {
+    let len = path.len();
+    path.push('/');
+    path.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+    let result = f(path);
+    path.truncate(len);
+    result
+}
+
+/// 差异项与相同的叶子节点数
+#[derive(Default)]
+struct Output {
+    items: Vec<DiffItem>,
+    equal_leaves: usize,
+}
+
+impl Output {
+    fn push(&mut self, path: &str, diff_type: DiffType, left: Option<&Value>, right: Option<&Value>) {
+        let (content, original_content) = match (left, right) {
+            (Some(left), None) => (left.to_string(), None),
+            (None, Some(right)) => (right.to_string(), None),
+            (Some(left), Some(right)) => {
+                let original = (diff_type != DiffType::Equal || left != right).then(|| left.to_string());
+                (right.to_string(), original)
+            }
+            (None, None) => return,
+        };
+
+        self.items.push(DiffItem {
+            id: format!("diff-{}", self.items.len()),
+            diff_type,
+            content,
+            original_content,
+            line_number: None,
+            position: Position::default(),
+            inline_changes: Vec::new(),
+            left_line: None,
+            right_line: None,
+            path: Some(path.to_string()),
+        });
+    }
+
+    /// 变化计数与字词数同文本对比；相似度与编辑距离按叶子节点（标量与空容器）计算
+    fn stats(&self, left: &Value, right: &Value) -> DiffStats {
+        let mut builder = StatsBuilder::default();
+        builder.add_items(&self.items);
+        let mut stats = builder.finish("", "");
+
+        let (left_leaves, right_leaves) = (leaves(left), leaves(right));
+        let longer = left_leaves.len().max(right_leaves.len());
+        let left_set: HashSet<&(String, String)> = left_leaves.iter().collect();
+        let right_set: HashSet<&(String, String)> = right_leaves.iter().collect();
+
+        stats.similarity = percentage(self.equal_leaves * 2, left_leaves.len() + right_leaves.len());
+        stats.edit_distance = longer.saturating_sub(self.equal_leaves);
+        stats.levenshtein_similarity = percentage(self.equal_leaves, longer);
+        stats.jaccard_similarity = percentage(left_set.intersection(&right_set).count(), left_set.union(&right_set).count());
+        stats
+    }
+}
+
+/// 叶子节点的路径与值
+fn leaves(value: &Value) -> Vec<(String, String)> {
+    fn collect(path: &mut String, value: &Value, out: &mut Vec<(String, String)>) {
+        match value {
+            Value::Object(map) if !map.is_empty() => {
+                for (key, value) in map {
+                    with_segment(path, key, |path| collect(path, value, out));
+                }
+            }
+            Value::Array(array) if !array.is_empty() => {
+                for (i, value) in array.iter().enumerate() {
+                    with_segment(path, &i.to_string(), |path| collect(path, value, out));
+                }
+            }
+            _ => out.push((path.clone(), value.to_string())),
+        }
+    }
+
+    let mut out = Vec::new();
+    collect(&mut String::new(), value, &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff_engine::{DiffEngine, DiffOptions};
+
+    fn structured_options(format: StructuredFormat) -> DiffOptions {
+        DiffOptions {
+            structured: Some(format),
+            ..Default::default()
+        }
+    }
+
+    fn changes(items: &[DiffItem]) -> Vec<(DiffType, &str)> {
+        items
+            .iter()
+            .filter(|item| item.diff_type != DiffType::Equal)
+            .map(|item| (item.diff_type, item.path.as_deref().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_key_order_is_ignored_and_paths_are_pointers() {
+        let left = r#"{"name": "app", "a/b": 1, "servers": [{"id": "x", "port": 80}, {"id": "y", "port": 81}]}"#;
+        let right = r#"{"servers": [{"id": "y", "port": 81}, {"port": 8080, "id": "x"}], "name": "app", "debug": true}"#;
+
+        let engine = DiffEngine::new(structured_options(StructuredFormat::Json));
+        let result = engine.try_compute_diff(left, right).unwrap();
+
+        // 数组按 id 配对，只有端口变化；路径取右侧下标
+        assert_eq!(
+            changes(&result.items),
+            vec![(DiffType::Remove, "/a~1b"), (DiffType::Add, "/debug"), (DiffType::Modify, "/servers/1/port")]
+        );
+        let modified = result.items.iter().find(|item| item.diff_type == DiffType::Modify).unwrap();
+        assert_eq!((modified.original_content.as_deref(), modified.content.as_str()), (Some("80"), "8080"));
+        assert_eq!(result.stats.modifications, 1);
+    }
+
+    #[test]
+    fn test_yaml_and_toml_arrays_without_ids() {
+        let engine = DiffEngine::new(structured_options(StructuredFormat::Yaml));
+        let result = engine.compute_diff("hosts:\n  - a\n  - b\n", "hosts:\n  - z\n  - a\n  - b\n");
+        assert_eq!(changes(&result.items), vec![(DiffType::Add, "/hosts/0")]);
+
+        let engine = DiffEngine::new(structured_options(StructuredFormat::Toml));
+        let left = "[server]\nport = 80\nstarted = 1979-05-27T07:32:00Z\n";
+        let right = "[server]\nstarted = 1979-05-27T07:32:00Z\nport = 81\n";
+        let result = engine.compute_diff(left, right);
+        assert_eq!(changes(&result.items), vec![(DiffType::Modify, "/server/port")]);
+
+        let result = engine.try_compute_diff("[server", right);
+        assert!(matches!(result, Err(DiffError::InvalidDocument(_))));
+    }
+
+    #[test]
+    fn test_ignore_options_apply_to_keys_and_ids() {
+        let left = r#"{"Name": "app", "servers": [{"id": "WEB", "port": 80}, {"id": "db", "port": 5432}]}"#;
+        let right = r#"{"name": "APP", "servers": [{"id": "DB", "port": 5432}, {"id": "web", "port": 8080}]}"#;
+
+        let engine = DiffEngine::new(DiffOptions {
+            ignore_case: true,
+            ..structured_options(StructuredFormat::Json)
+        });
+        let result = engine.try_compute_diff(left, right).unwrap();
+
+        // 键与配对值忽略大小写后配对，只有端口变化
+        assert_eq!(changes(&result.items), vec![(DiffType::Modify, "/servers/1/port")]);
+
+        // 规范化后同一对象中有重复的键时按原样比较
+        let result = engine.try_compute_diff(r#"{"a": 1, "A": 2}"#, r#"{"A": 3, "a": 1}"#).unwrap();
+        assert_eq!(changes(&result.items), vec![(DiffType::Modify, "/A")]);
+    }
+}
@@ -0,0 +1,301 @@
+// 表格对比：列按表头配对，行按键列（未指定时按行序列）配对，报告增删的行列与变化的单元格
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::algorithm::{intern, SequenceDiff};
+use super::control::DiffError;
+use super::normalize::Normalizer;
+use super::ops::pair_elements;
+use super::DiffType;
+
+/// 表格：首行为表头，各行的单元格数可以不同，缺少的单元格视为空
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    pub fn cell(&self, row: usize, column: usize) -> &str {
+        self.rows[row].get(column).map_or("", String::as_str)
+    }
+
+    /// 按表头查找列，重名时取第一列
+    pub fn column(&self, name: &str) -> Option<usize> {
+        self.headers.iter().position(|header| header == name)
+    }
+}
+
+/// 对比结果中的一列：两侧都有的列为 `Equal`，只在一侧的列为 `Add` 或 `Remove`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableColumn {
+    pub name: String,
+    pub diff_type: DiffType,
+    pub left: Option<usize>,
+    pub right: Option<usize>,
+}
+
+/// 单元格：新增、删除的行列中为 `Add` 或 `Remove`（两侧都没有值时为 `Equal`），其余为 `Equal` 或 `Modify`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableCell {
+    pub diff_type: DiffType,
+    pub left: Option<String>,
+    pub right: Option<String>,
+}
+
+/// 对比结果中的一行，单元格与 `TableDiff::columns` 一一对应；行号为各侧数据行的下标（不含表头）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableRow {
+    pub diff_type: DiffType,
+    /// 按键列配对时的键值
+    pub key: Option<String>,
+    pub left: Option<usize>,
+    pub right: Option<usize>,
+    pub cells: Vec<TableCell>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableStats {
+    pub added_rows: usize,
+    pub removed_rows: usize,
+    pub modified_rows: usize,
+    pub unchanged_rows: usize,
+    /// 两侧都有的行列中内容变化的单元格数
+    pub changed_cells: usize,
+    pub added_columns: usize,
+    pub removed_columns: usize,
+}
+
+/// 表格对比结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableDiff {
+    pub key_column: Option<String>,
+    pub columns: Vec<TableColumn>,
+    pub rows: Vec<TableRow>,
+    pub stats: TableStats,
+}
+
+/// 对比两张表格
+///
+/// 列按表头名配对，列的顺序不计；行在指定键列时按键值配对（重复的键按出现顺序配对），
+/// 否则按整行内容做序列对比，相邻的删除与新增逐行配对为修改。
+/// 输出按右侧顺序排列，只在左侧的行列排在其左侧前一个已配对的行列之后。
+pub fn diff_tables(
+    left: &Table,
+    right: &Table,
+    key_column: Option<&str>,
+    normalizer: &Normalizer,
+    algorithm: &dyn SequenceDiff,
+) -> Result<TableDiff, DiffError> {
+    let columns = match_columns(left, right);
+    let same = |a: &str, b: &str| normalizer.key(a) == normalizer.key(b);
+
+    let (pairs, keys) = match key_column {
+        Some(name) => {
+            let missing = || DiffError::MissingColumn(name.to_string());
+            let (left_key, right_key) = (left.column(name).ok_or_else(missing)?, right.column(name).ok_or_else(missing)?);
+            let left_keys = occurrence_keys(left.rows.len(), |i| normalizer.key(left.cell(i, left_key)).into_owned());
+            let right_keys = occurrence_keys(right.rows.len(), |j| normalizer.key(right.cell(j, right_key)).into_owned());
+            let pairs = pair_by_key(&left_keys, &right_keys);
+            (pairs, Some((left_key, right_key)))
+        }
+        None => (pair_by_sequence(left, right, normalizer, algorithm), None),
+    };
+
+    let mut stats = TableStats {
+        added_columns: columns.iter().filter(|column| column.diff_type == DiffType::Add).count(),
+        removed_columns: columns.iter().filter(|column| column.diff_type == DiffType::Remove).count(),
+        ..Default::default()
+    };
+
+    let rows = pairs
+        .into_iter()
+        .map(|(i, j)| {
+            let cells: Vec<TableCell> = columns
+                .iter()
+                .map(|column| {
+                    let left_value = i.zip(column.left).map(|(i, c)| left.cell(i, c).to_string());
+                    let right_value = j.zip(column.right).map(|(j, c)| right.cell(j, c).to_string());
+                    let diff_type = match (&left_value, &right_value) {
+                        (Some(a), Some(b)) if same(a, b) => DiffType::Equal,
+                        (Some(_), Some(_)) => DiffType::Modify,
+                        (Some(_), None) => DiffType::Remove,
+                        (None, Some(_)) => DiffType::Add,
+                        // 删除的行在新增的列中（或新增的行在删除的列中）没有单元格
+                        (None, None) => DiffType::Equal,
+                    };
+                    TableCell { diff_type, left: left_value, right: right_value }
+                })
+                .collect();
+
+            let diff_type = match (i, j) {
+                (Some(_), None) => DiffType::Remove,
+                (None, _) => DiffType::Add,
+                _ if cells.iter().any(|cell| cell.diff_type == DiffType::Modify) => DiffType::Modify,
+                _ => DiffType::Equal,
+            };
+            match diff_type {
+                DiffType::Add => stats.added_rows += 1,
+                DiffType::Remove => stats.removed_rows += 1,
+                DiffType::Modify => stats.modified_rows += 1,
+                _ => stats.unchanged_rows += 1,
+            }
+            stats.changed_cells += cells.iter().filter(|cell| cell.diff_type == DiffType::Modify).count();
+
+            let key = keys.map(|(left_key, right_key)| match j {
+                Some(j) => right.cell(j, right_key).to_string(),
+                None => left.cell(i.unwrap_or_default(), left_key).to_string(),
+            });
+            TableRow { diff_type, key, left: i, right: j, cells }
+        })
+        .collect();
+
+    Ok(TableDiff {
+        key_column: key_column.map(String::from),
+        columns,
+        rows,
+        stats,
+    })
+}
+
+/// 列按表头名配对，重名的列按出现顺序配对
+fn match_columns(left: &Table, right: &Table) -> Vec<TableColumn> {
+    let left_keys = occurrence_keys(left.headers.len(), |i| left.headers[i].clone());
+    let right_keys = occurrence_keys(right.headers.len(), |j| right.headers[j].clone());
+
+    pair_by_key(&left_keys, &right_keys)
+        .into_iter()
+        .map(|(left_index, right_index)| {
+            let (name, diff_type) = match (left_index, right_index) {
+                (Some(_), Some(j)) => (&right.headers[j], DiffType::Equal),
+                (None, Some(j)) => (&right.headers[j], DiffType::Add),
+                (Some(i), None) => (&left.headers[i], DiffType::Remove),
+                (None, None) => unreachable!("配对结果至少含一侧"),
+            };
+            TableColumn {
+                name: name.clone(),
+                diff_type,
+                left: left_index,
+                right: right_index,
+            }
+        })
+        .collect()
+}
+
+/// 键值加上同键的出现次序，使重复的键按顺序一一配对
+fn occurrence_keys(len: usize, key: impl Fn(usize) -> String) -> Vec<(String, usize)> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    (0..len)
+        .map(|index| {
+            let key = key(index);
+            let occurrence = seen.entry(key.clone()).or_default();
+            *occurrence += 1;
+            (key, *occurrence)
+        })
+        .collect()
+}
+
+/// 按键配对，输出右侧顺序，只在左侧的元素跟在其左侧前一个已配对元素之后
+fn pair_by_key<K: Eq + std::hash::Hash>(left: &[K], right: &[K]) -> Vec<(Option<usize>, Option<usize>)> {
+    let right_index: HashMap<&K, usize> = right.iter().enumerate().map(|(j, key)| (key, j)).collect();
+    let matched: Vec<Option<usize>> = left.iter().map(|key| right_index.get(key).copied()).collect();
+
+    // 只在左侧的元素，按其前一个已配对元素在右侧的位置归组
+    let mut removed_after: HashMap<Option<usize>, Vec<usize>> = HashMap::new();
+    let mut last_matched = None;
+    for (i, matched) in matched.iter().enumerate() {
+        match matched {
+            Some(j) => last_matched = Some(*j),
+            None => removed_after.entry(last_matched).or_default().push(i),
+        }
+    }
+
+    let mut left_of: Vec<Option<usize>> = vec![None; right.len()];
+    for (i, j) in matched.iter().enumerate() {
+        if let Some(j) = j {
+            left_of[*j] = Some(i);
+        }
+    }
+
+    let mut pairs: Vec<(Option<usize>, Option<usize>)> =
+        removed_after.remove(&None).unwrap_or_default().into_iter().map(|i| (Some(i), None)).collect();
+    for (j, i) in left_of.into_iter().enumerate() {
+        pairs.push((i, Some(j)));
+        pairs.extend(removed_after.remove(&Some(j)).unwrap_or_default().into_iter().map(|i| (Some(i), None)));
+    }
+    pairs
+}
+
+/// 按整行内容做序列对比，相邻的删除与新增逐行配对为修改
+fn pair_by_sequence(
+    left: &Table,
+    right: &Table,
+    normalizer: &Normalizer,
+    algorithm: &dyn SequenceDiff,
+) -> Vec<(Option<usize>, Option<usize>)> {
+    let row_key = |row: &Vec<String>| row.iter().map(|cell| normalizer.key(cell)).collect::<Vec<_>>().join("\t");
+    let left_keys: Vec<String> = left.rows.iter().map(row_key).collect();
+    let right_keys: Vec<String> = right.rows.iter().map(row_key).collect();
+    let (left_ids, right_ids) = intern(
+        &left_keys.iter().map(String::as_str).collect::<Vec<_>>(),
+        &right_keys.iter().map(String::as_str).collect::<Vec<_>>(),
+    );
+
+    pair_elements(&algorithm.diff(&left_ids, &right_ids))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff_engine::{DiffEngine, DiffOptions};
+
+    fn table(rows: &[&[&str]]) -> Table {
+        let mut rows = rows.iter().map(|row| row.iter().map(|cell| cell.to_string()).collect::<Vec<_>>());
+        Table {
+            headers: rows.next().unwrap_or_default(),
+            rows: rows.collect(),
+        }
+    }
+
+    #[test]
+    fn test_rows_match_by_key_column() {
+        let left = table(&[&["编号", "名称", "单价", "备注"], &["1", "苹果", "5", ""], &["2", "香蕉", "3", ""], &["3", "梨", "4", ""]]);
+        let right = table(&[&["名称", "编号", "单价", "产地"], &["梨", "3", "4", "河北"], &["苹果", "1", "6", "山东"], &["桃", "4", "7", "浙江"]]);
+
+        let engine = DiffEngine::new(DiffOptions::default());
+        let diff = engine.compute_table_diff(&left, &right, Some("编号")).unwrap();
+
+        let columns: Vec<_> = diff.columns.iter().map(|column| (column.name.as_str(), column.diff_type)).collect();
+        assert_eq!(
+            columns,
+            vec![("名称", DiffType::Equal), ("编号", DiffType::Equal), ("单价", DiffType::Equal), ("备注", DiffType::Remove), ("产地", DiffType::Add)]
+        );
+
+        // 行的顺序变化不算改动，只有苹果的单价变了；香蕉排在其左侧前一行（苹果）之后
+        let rows: Vec<_> = diff.rows.iter().map(|row| (row.key.as_deref().unwrap(), row.diff_type)).collect();
+        assert_eq!(rows, vec![("3", DiffType::Equal), ("1", DiffType::Modify), ("2", DiffType::Remove), ("4", DiffType::Add)]);
+        let price = &diff.rows[1].cells[2];
+        assert_eq!((price.left.as_deref(), price.right.as_deref()), (Some("5"), Some("6")));
+
+        assert_eq!(diff.stats.changed_cells, 1);
+        assert_eq!((diff.stats.added_columns, diff.stats.removed_columns), (1, 1));
+
+        assert!(matches!(engine.compute_table_diff(&left, &right, Some("型号")), Err(DiffError::MissingColumn(_))));
+    }
+
+    #[test]
+    fn test_rows_without_key_are_diffed_in_order() {
+        let left = table(&[&["a", "b"], &["1", "x"], &["2", "y"], &["3", "z"]]);
+        let right = table(&[&["a", "b"], &["1", "x"], &["2", "Y"], &["3", "z"], &["4", "w"]]);
+
+        let engine = DiffEngine::new(DiffOptions::default());
+        let diff = engine.compute_table_diff(&left, &right, None).unwrap();
+        let kinds: Vec<_> = diff.rows.iter().map(|row| row.diff_type).collect();
+        assert_eq!(kinds, vec![DiffType::Equal, DiffType::Modify, DiffType::Equal, DiffType::Add]);
+
+        let engine = DiffEngine::new(DiffOptions { ignore_case: true, ..Default::default() });
+        let diff = engine.compute_table_diff(&left, &right, None).unwrap();
+        assert_eq!(diff.stats.modified_rows, 0);
+    }
+}
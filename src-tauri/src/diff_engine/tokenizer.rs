@@ -0,0 +1,213 @@
+// 分词器：拉丁文按词边界切分，中日韩文字按词典做双向最大匹配
+use std::collections::HashSet;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+/// 内置中文词典
+const BUILTIN_DICTIONARY: &str = include_str!("dict/zh_words.txt");
+
+/// 判断是否为中文字符：表意文字或中日韩标点
+pub fn is_chinese_char(c: char) -> bool {
+    is_cjk_ideograph(c) || is_cjk_punctuation(c)
+}
+
+/// CJK 统一表意文字（含扩展 A–H）及兼容表意文字
+pub fn is_cjk_ideograph(c: char) -> bool {
+    matches!(c,
+        '\u{4e00}'..='\u{9fff}'      // 基本区
+        | '\u{3400}'..='\u{4dbf}'    // 扩展 A
+        | '\u{20000}'..='\u{2a6df}'  // 扩展 B
+        | '\u{2a700}'..='\u{2ebef}'  // 扩展 C–F
+        | '\u{30000}'..='\u{323af}'  // 扩展 G–H
+        | '\u{f900}'..='\u{faff}'    // 兼容表意文字
+        | '\u{2f800}'..='\u{2fa1f}'  // 兼容表意文字补充
+    )
+}
+
+/// 中日韩标点：CJK 符号和标点区，以及全角形式中的标点
+pub fn is_cjk_punctuation(c: char) -> bool {
+    matches!(c,
+        '\u{3001}'..='\u{303f}'
+        | '\u{ff01}'..='\u{ff0f}'
+        | '\u{ff1a}'..='\u{ff20}'
+        | '\u{ff3b}'..='\u{ff40}'
+        | '\u{ff5b}'..='\u{ff65}'
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Word,
+    Space,
+    Cjk,
+    Other,
+}
+
+fn classify(c: char) -> CharClass {
+    if is_cjk_ideograph(c) {
+        CharClass::Cjk
+    } else if c.is_whitespace() {
+        CharClass::Space
+    } else if c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Other
+    }
+}
+
+/// 词级分词器
+///
+/// 输出的词元区间首尾相接、覆盖全文：连续空白为一个词元，标点每个字符一个词元。
+pub struct Tokenizer {
+    dictionary: HashSet<String>,
+    max_word_chars: usize,
+}
+
+impl Default for Tokenizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tokenizer {
+    /// 创建带内置词典的分词器
+    pub fn new() -> Self {
+        let mut tokenizer = Self {
+            dictionary: HashSet::new(),
+            max_word_chars: 1,
+        };
+        tokenizer.add_words(
+            BUILTIN_DICTIONARY
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#')),
+        );
+        tokenizer
+    }
+
+    /// 共享的内置分词器实例
+    pub fn builtin() -> &'static Tokenizer {
+        static TOKENIZER: OnceLock<Tokenizer> = OnceLock::new();
+        TOKENIZER.get_or_init(Tokenizer::new)
+    }
+
+    /// 追加用户词典
+    pub fn add_words<'a>(&mut self, words: impl IntoIterator<Item = &'a str>) {
+        for word in words {
+            self.max_word_chars = self.max_word_chars.max(word.chars().count());
+            self.dictionary.insert(word.to_string());
+        }
+    }
+
+    /// 切分文本，返回每个词元的字节区间
+    pub fn tokenize(&self, text: &str) -> Vec<Range<usize>> {
+        let mut tokens = Vec::new();
+        let chars: Vec<(usize, char)> = text.char_indices().collect();
+        let mut i = 0;
+
+        while i < chars.len() {
+            let (start, c) = chars[i];
+            let class = classify(c);
+            let mut j = i + 1;
+
+            match class {
+                CharClass::Other => {}
+                CharClass::Word => {
+                    while j < chars.len() {
+                        let next = chars[j].1;
+                        // 词内撇号（don't、O’Neil）不切分
+                        let inner_apostrophe = (next == '\'' || next == '’')
+                            && chars.get(j + 1).is_some_and(|&(_, after)| classify(after) == CharClass::Word);
+                        if classify(next) == CharClass::Word || inner_apostrophe {
+                            j += 1;
+                        } else {
+                            break;
+                        }
+                    }
+                }
+                CharClass::Space | CharClass::Cjk => {
+                    while j < chars.len() && classify(chars[j].1) == class {
+                        j += 1;
+                    }
+                }
+            }
+
+            let end = chars.get(j).map_or(text.len(), |&(offset, _)| offset);
+
+            if class == CharClass::Cjk {
+                self.segment_cjk(text, start, end, &mut tokens);
+            } else {
+                tokens.push(start..end);
+            }
+
+            i = j;
+        }
+
+        tokens
+    }
+
+    /// 对一段连续的中文做双向最大匹配，取词数更少（其次单字更少）的切分
+    fn segment_cjk(&self, text: &str, start: usize, end: usize, tokens: &mut Vec<Range<usize>>) {
+        // bounds[k] 为第 k 个字符的字节起点，末尾追加 end
+        let mut bounds: Vec<usize> = text[start..end].char_indices().map(|(i, _)| start + i).collect();
+        bounds.push(end);
+        let n = bounds.len() - 1;
+
+        let is_word = |from: usize, to: usize| to - from == 1 || self.dictionary.contains(&text[bounds[from]..bounds[to]]);
+
+        // 正向最大匹配
+        let mut forward = Vec::new();
+        let mut i = 0;
+        while i < n {
+            let len = (1..=self.max_word_chars.min(n - i))
+                .rev()
+                .find(|&len| is_word(i, i + len))
+                .unwrap_or(1);
+            forward.push(i..i + len);
+            i += len;
+        }
+
+        // 逆向最大匹配
+        let mut backward = Vec::new();
+        let mut j = n;
+        while j > 0 {
+            let len = (1..=self.max_word_chars.min(j))
+                .rev()
+                .find(|&len| is_word(j - len, j))
+                .unwrap_or(1);
+            backward.push(j - len..j);
+            j -= len;
+        }
+        backward.reverse();
+
+        let singles = |words: &[Range<usize>]| words.iter().filter(|w| w.len() == 1).count();
+        let chosen = if (forward.len(), singles(&forward)) < (backward.len(), singles(&backward)) {
+            forward
+        } else {
+            backward
+        };
+
+        tokens.extend(chosen.into_iter().map(|w| bounds[w.start]..bounds[w.end]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str) -> Vec<&str> {
+        Tokenizer::builtin()
+            .tokenize(text)
+            .into_iter()
+            .map(|r| &text[r])
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_mixed_text() {
+        assert_eq!(
+            words("甲方应当支付违约金, don't delay."),
+            vec!["甲方", "应当", "支付", "违约金", ",", " ", "don't", " ", "delay", "."]
+        );
+    }
+}
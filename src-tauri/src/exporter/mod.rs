@@ -1,10 +1,16 @@
 // 导出模块
 use std::fs::File;
 use std::io::Write;
+use std::ops::Range;
 use std::path::Path;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use crate::diff_engine::{DiffItem, DiffStats, DiffType};
+use crate::diff_engine::{
+    align_rows, source_texts, tokenize_code, CodeLanguage, CodeToken, DiffItem, DiffMetadata, DiffResult, DiffStats,
+    DiffType, PatchOptions, RowCell, TokenKind,
+};
+
+mod table;
 
 #[derive(Error, Debug)]
 pub enum ExportError {
@@ -26,6 +32,12 @@ pub struct ExportOptions {
     pub include_metadata: bool,
     pub template: Option<String>,
     pub styles: ExportStyles,
+    /// HTML 导出按行对齐为左右两栏
+    #[serde(default)]
+    pub side_by_side: bool,
+    /// HTML 导出按该语言语法高亮，差异以行内标记显示在完整代码中
+    #[serde(default)]
+    pub code_language: Option<CodeLanguage>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +48,8 @@ pub enum ExportFormat {
     Text,
     Json,
     Markdown,
+    /// 仅用于表格对比结果
+    Xlsx,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,6 +57,8 @@ pub struct ExportStyles {
     pub add_color: String,
     pub remove_color: String,
     pub modify_color: String,
+    #[serde(default = "default_move_color")]
+    pub move_color: String,
     pub font_family: String,
     pub font_size: String,
 }
@@ -53,12 +69,17 @@ impl Default for ExportStyles {
             add_color: "#22c55e",
             remove_color: "#ef4444",
             modify_color: "#3b82f6",
+            move_color: default_move_color(),
             font_family: "system-ui, -apple-system, sans-serif",
             font_size: "14px",
         }
     }
 }
 
+fn default_move_color() -> String {
+    "#8b5cf6".to_string()
+}
+
 /// 导出器主接口
 pub struct Exporter {
     options: ExportOptions,
@@ -83,6 +104,7 @@ impl Exporter {
             ExportFormat::Text => self.export_text(items, stats, output_path).await,
             ExportFormat::Json => self.export_json(items, stats, output_path).await,
             ExportFormat::Markdown => self.export_markdown(items, stats, output_path).await,
+            ExportFormat::Xlsx => Err(ExportError::ExportFailed("XLSX 仅用于导出表格对比结果".to_string())),
         }
     }
     
@@ -200,12 +222,114 @@ impl Exporter {
             border-left: 3px solid {};
         }}
         
+        .diff-move {{
+            background: #f3e8ff;
+            border-left: 3px solid {};
+        }}
+        
+        .move-source {{
+            color: #7c3aed;
+            font-size: 12px;
+            margin-left: 10px;
+        }}
+        
+        .inline-add {{
+            background: #acf2bd;
+            text-decoration: none;
+        }}
+        
+        .inline-remove {{
+            background: #fdb8c0;
+            text-decoration: line-through;
+        }}
+        
         .diff-equal {{
             color: #666;
             font-size: 12px;
             opacity: 0.6;
         }}
         
+        .side-by-side {{
+            width: 100%;
+            border-collapse: collapse;
+            table-layout: fixed;
+            font-family: 'Consolas', 'Monaco', monospace;
+            font-size: 13px;
+        }}
+        
+        .side-by-side td {{
+            padding: 2px 6px;
+            vertical-align: top;
+            white-space: pre-wrap;
+            word-wrap: break-word;
+        }}
+        
+        .side-by-side td.line-number {{
+            width: 50px;
+            display: table-cell;
+            border-right: 1px solid #e0e0e0;
+        }}
+        
+        .row-add td.right {{
+            background: #e6ffed;
+        }}
+        
+        .row-remove td.left {{
+            background: #ffebe9;
+        }}
+        
+        .row-modify td.left, .row-modify td.right {{
+            background: #f0f9ff;
+        }}
+        
+        .row-move td.left, .row-move td.right {{
+            background: #faf5ff;
+        }}
+        
+        .side-by-side td.filler {{
+            background: repeating-linear-gradient(45deg, #fafafa, #fafafa 4px, #f0f0f0 4px, #f0f0f0 8px);
+        }}
+        
+        .inline-move {{
+            background: #e9d5ff;
+        }}
+        
+        .inline-modify {{
+            background: #bfdbfe;
+        }}
+        
+        .code-diff {{
+            font-family: 'Consolas', 'Monaco', monospace;
+            font-size: 13px;
+            line-height: 1.5;
+            white-space: pre-wrap;
+            word-wrap: break-word;
+        }}
+        
+        .tok-keyword {{
+            color: #cf222e;
+        }}
+        
+        .tok-string {{
+            color: #0a3069;
+        }}
+        
+        .tok-number {{
+            color: #0550ae;
+        }}
+        
+        .tok-comment {{
+            color: #6e7781;
+            font-style: italic;
+        }}
+        
+        .json-path {{
+            display: inline-block;
+            color: #0969da;
+            font-family: 'Consolas', 'Monaco', monospace;
+            margin-right: 10px;
+        }}
+        
         .line-number {{
             display: inline-block;
             width: 50px;
@@ -244,7 +368,8 @@ impl Exporter {
             self.options.styles.font_size,
             self.options.styles.add_color,
             self.options.styles.remove_color,
-            self.options.styles.modify_color
+            self.options.styles.modify_color,
+            self.options.styles.move_color
         ));
         
         // 时间戳
@@ -277,6 +402,10 @@ impl Exporter {
                 <div class="stat-value">{}</div>
                 <div class="stat-label">修改</div>
             </div>
+            <div class="stat-item">
+                <div class="stat-value">{}</div>
+                <div class="stat-label">移动</div>
+            </div>
             <div class="stat-item">
                 <div class="stat-value">{:.1}%</div>
                 <div class="stat-label">相似度</div>
@@ -286,32 +415,56 @@ impl Exporter {
                 stats.additions,
                 stats.deletions,
                 stats.modifications,
+                stats.moves,
                 stats.similarity
             ));
         }
         
-        // 差异内容
+        // 差异内容：并排模式按行对齐为左右两栏，否则逐项列出
         html.push_str(r#"
         <div class="content">"#);
         
-        for item in items {
+        let itemized = !self.options.side_by_side && self.options.code_language.is_none();
+        if self.options.side_by_side {
+            html.push_str(&self.render_side_by_side_html(items));
+        } else if let Some(language) = self.options.code_language {
+            html.push_str(&self.render_code_html(items, language));
+        }
+        
+        for item in items.iter().filter(|_| itemized) {
             let class = match item.diff_type {
                 DiffType::Add => "diff-add",
                 DiffType::Remove => "diff-remove",
                 DiffType::Modify => "diff-modify",
                 DiffType::Equal => "diff-equal",
+                DiffType::Move => "diff-move",
             };
             
+            // 结构化对比的差异项没有行号，标注 JSON Pointer 路径
             let line_number = item.line_number
                 .map(|n| format!(r#"<span class="line-number">{}</span>"#, n))
+                .or_else(|| self.item_path(item).map(|path| {
+                    format!(r#"<span class="json-path">{}</span>"#, html_escape::encode_text(path))
+                }))
                 .unwrap_or_default();
             
-            let content = html_escape::encode_text(&item.content);
+            let content = if item.inline_changes.is_empty() {
+                html_escape::encode_text(&item.content).into_owned()
+            } else {
+                self.render_inline_html(&item.inline_changes)
+            };
+            
+            // 移动项标注来源位置
+            let move_source = if item.diff_type == DiffType::Move {
+                format!(r#"<span class="move-source">← 移自第 {} 行</span>"#, item.position.left.start_line)
+            } else {
+                String::new()
+            };
             
             html.push_str(&format!(r#"
             <div class="diff-item {}">
-                {}{}
-            </div>"#, class, line_number, content));
+                {}{}{}
+            </div>"#, class, line_number, content, move_source));
         }
         
         html.push_str(r#"
@@ -418,6 +571,25 @@ impl Exporter {
                 );
             }
             
+            if item.diff_type == DiffType::Move {
+                paragraph = paragraph.add_run(
+                    Run::new()
+                        .add_text(format!("[移自第 {} 行] ", item.position.left.start_line))
+                        .color("8b5cf6")
+                );
+            }
+            
+            if matches!(item.diff_type, DiffType::Modify | DiffType::Move) && !item.inline_changes.is_empty() {
+                // 修改项内部差异逐段着色
+                for change in &item.inline_changes {
+                    for run in self.inline_docx_runs(change) {
+                        paragraph = paragraph.add_run(run);
+                    }
+                }
+                docx = docx.add_paragraph(paragraph);
+                continue;
+            }
+            
             // 根据差异类型设置样式
             let run = match item.diff_type {
                 DiffType::Add => {
@@ -448,6 +620,12 @@ impl Exporter {
                         .color("3b82f6")
                         .underline("single")
                 }
+                DiffType::Move => {
+                    Run::new()
+                        .add_text(&item.content)
+                        .color("8b5cf6")
+                        .underline("double")
+                }
                 DiffType::Equal => {
                     // 相同内容使用较小字体和灰色
                     Run::new()
@@ -495,6 +673,7 @@ impl Exporter {
             text.push_str(&format!("新增: {} 项, {} 词\n", stats.additions, stats.added_words));
             text.push_str(&format!("删除: {} 项, {} 词\n", stats.deletions, stats.deleted_words));
             text.push_str(&format!("修改: {} 项\n", stats.modifications));
+            text.push_str(&format!("移动: {} 项\n", stats.moves));
             text.push_str(&format!("相似度: {:.2}%\n\n", stats.similarity));
         }
         
@@ -502,8 +681,13 @@ impl Exporter {
         text.push_str(&"-".repeat(30));
         text.push('\n');
         
+        // 行级对比结果输出为统一格式，其余差异项逐条列出
+        if items.iter().any(|item| item.left_line.is_some()) {
+            text.push_str(&self.render_line_diff(items));
+        }
+        
         let mut index = 0;
-        for item in items {
+        for item in items.iter().filter(|item| item.left_line.is_none()) {
             if matches!(item.diff_type, DiffType::Equal) {
                 continue;
             }
@@ -511,6 +695,7 @@ impl Exporter {
             index += 1;
             let line_info = item.line_number
                 .map(|n| format!("[行 {}] ", n))
+                .or_else(|| self.item_path(item).map(|path| format!("[{}] ", path)))
                 .unwrap_or_default();
             
             let type_label = match item.diff_type {
@@ -518,6 +703,7 @@ impl Exporter {
                 DiffType::Remove => "[删除]",
                 DiffType::Modify => "[修改]",
                 DiffType::Equal => "[相同]",
+                DiffType::Move => "[移动]",
             };
             
             text.push_str(&format!("{}. {}{}\n", index, line_info, type_label));
@@ -527,6 +713,24 @@ impl Exporter {
                     text.push_str(&format!("   原文: {}\n", original));
                     text.push_str(&format!("   现文: {}\n", item.content));
                 }
+                if !item.inline_changes.is_empty() {
+                    text.push_str(&format!(
+                        "   变更: {}\n",
+                        self.render_inline_marked(&item.inline_changes, ("[-", "-]"), ("{+", "+}"))
+                    ));
+                }
+            } else if item.diff_type == DiffType::Move {
+                text.push_str(&format!(
+                    "   来源: 第 {} 行 → 目标: 第 {} 行\n",
+                    item.position.left.start_line, item.position.right.start_line
+                ));
+                text.push_str(&format!("   内容: {}\n", item.content));
+                if !item.inline_changes.is_empty() {
+                    text.push_str(&format!(
+                        "   变更: {}\n",
+                        self.render_inline_marked(&item.inline_changes, ("[-", "-]"), ("{+", "+}"))
+                    ));
+                }
             } else {
                 text.push_str(&format!("   内容: {}\n", item.content));
             }
@@ -615,6 +819,7 @@ impl Exporter {
             markdown.push_str(&format!("| 新增 | {} 项 ({} 词) |\n", stats.additions, stats.added_words));
             markdown.push_str(&format!("| 删除 | {} 项 ({} 词) |\n", stats.deletions, stats.deleted_words));
             markdown.push_str(&format!("| 修改 | {} 项 |\n", stats.modifications));
+            markdown.push_str(&format!("| 移动 | {} 项 |\n", stats.moves));
             markdown.push_str(&format!("| 相似度 | {:.2}% |\n\n", stats.similarity));
         }
         
@@ -630,10 +835,12 @@ impl Exporter {
                 DiffType::Remove => "➖",
                 DiffType::Modify => "✏️",
                 DiffType::Equal => "✅",
+                DiffType::Move => "🔀",
             };
             
             let line_info = item.line_number
                 .map(|n| format!(" (行 {})", n))
+                .or_else(|| self.item_path(item).map(|path| format!(" (`{}`)", path)))
                 .unwrap_or_default();
             
             markdown.push_str(&format!("### {} {}{}\n\n", 
@@ -643,6 +850,7 @@ impl Exporter {
                     DiffType::Remove => "删除",
                     DiffType::Modify => "修改",
                     DiffType::Equal => "相同",
+                    DiffType::Move => "移动",
                 },
                 line_info
             ));
@@ -652,6 +860,24 @@ impl Exporter {
                     markdown.push_str(&format!("**原文:**\n```\n{}\n```\n\n", original));
                     markdown.push_str(&format!("**现文:**\n```\n{}\n```\n\n", item.content));
                 }
+                if !item.inline_changes.is_empty() {
+                    markdown.push_str(&format!(
+                        "**变更:** {}\n\n",
+                        self.render_inline_marked(&item.inline_changes, ("~~", "~~"), ("**", "**"))
+                    ));
+                }
+            } else if item.diff_type == DiffType::Move {
+                markdown.push_str(&format!(
+                    "**来源:** 第 {} 行 → **目标:** 第 {} 行\n\n",
+                    item.position.left.start_line, item.position.right.start_line
+                ));
+                markdown.push_str(&format!("```\n{}\n```\n\n", item.content));
+                if !item.inline_changes.is_empty() {
+                    markdown.push_str(&format!(
+                        "**变更:** {}\n\n",
+                        self.render_inline_marked(&item.inline_changes, ("~~", "~~"), ("**", "**"))
+                    ));
+                }
             } else {
                 markdown.push_str(&format!("```\n{}\n```\n\n", item.content));
             }
@@ -662,4 +888,228 @@ impl Exporter {
         
         Ok(())
     }
+    
+    /// 渲染修改项内部的差异（HTML），嵌套的修改项递归展开
+    fn render_inline_html(&self, changes: &[DiffItem]) -> String {
+        let mut html = String::new();
+        
+        for change in changes {
+            let content = html_escape::encode_text(&change.content);
+            let original = html_escape::encode_text(change.original_content.as_deref().unwrap_or_default());
+            
+            match change.diff_type {
+                DiffType::Modify if !change.inline_changes.is_empty() => {
+                    html.push_str(&self.render_inline_html(&change.inline_changes));
+                }
+                DiffType::Equal => html.push_str(&content),
+                DiffType::Add | DiffType::Move => html.push_str(&format!(r#"<ins class="inline-add">{}</ins>"#, content)),
+                DiffType::Remove => html.push_str(&format!(r#"<del class="inline-remove">{}</del>"#, original)),
+                DiffType::Modify => html.push_str(&format!(
+                    r#"<del class="inline-remove">{}</del><ins class="inline-add">{}</ins>"#,
+                    original, content
+                )),
+            }
+        }
+        
+        html
+    }
+    
+    /// 代码视图：还原两侧源码后按语言着色，删除、新增与移动的部分以行内标记显示
+    fn render_code_html(&self, items: &[DiffItem], language: CodeLanguage) -> String {
+        let (left, right) = source_texts(items);
+        let left_tokens = tokenize_code(&left, language);
+        let right_tokens = tokenize_code(&right, language);
+        let mut html = String::from(r#"
+            <pre class="code-diff">"#);
+        
+        for item in items {
+            let left_range = item.position.left.byte_start..item.position.left.byte_end;
+            let right_range = item.position.right.byte_start..item.position.right.byte_end;
+            let removed = || format!(r#"<del class="inline-remove">{}</del>"#, self.highlight_code(&left, &left_tokens, left_range.clone()));
+            let added = || format!(r#"<ins class="inline-add">{}</ins>"#, self.highlight_code(&right, &right_tokens, right_range.clone()));
+            
+            match item.diff_type {
+                DiffType::Equal => html.push_str(&self.highlight_code(&right, &right_tokens, right_range.clone())),
+                DiffType::Add => html.push_str(&added()),
+                DiffType::Remove => html.push_str(&removed()),
+                DiffType::Modify => {
+                    html.push_str(&removed());
+                    html.push_str(&added());
+                }
+                DiffType::Move => html.push_str(&format!(
+                    r#"<span class="inline-move">{}</span>"#,
+                    self.highlight_code(&right, &right_tokens, right_range.clone())
+                )),
+            }
+        }
+        
+        html.push_str("</pre>");
+        html
+    }
+    
+    /// 按词元类别着色原文区间，区间边缘的词元只取区间内的部分
+    fn highlight_code(&self, text: &str, tokens: &[CodeToken], range: Range<usize>) -> String {
+        let first = tokens.partition_point(|token| token.range.end <= range.start);
+        let mut html = String::new();
+        
+        for token in tokens[first..].iter().take_while(|token| token.range.start < range.end) {
+            let part = &text[token.range.start.max(range.start)..token.range.end.min(range.end)];
+            let part = html_escape::encode_text(part);
+            let class = match token.kind {
+                TokenKind::Keyword => "tok-keyword",
+                TokenKind::String => "tok-string",
+                TokenKind::Number => "tok-number",
+                TokenKind::Comment => "tok-comment",
+                _ => {
+                    html.push_str(&part);
+                    continue;
+                }
+            };
+            html.push_str(&format!(r#"<span class="{}">{}</span>"#, class, part));
+        }
+        
+        html
+    }
+    
+    /// 结构化对比差异项的路径，整个文档（空路径）不标注
+    fn item_path<'a>(&self, item: &'a DiffItem) -> Option<&'a str> {
+        item.path.as_deref().filter(|path| !path.is_empty())
+    }
+    
+    /// 并排表格：由差异项还原两侧原文后按行对齐，缺行处渲染为填充格
+    fn render_side_by_side_html(&self, items: &[DiffItem]) -> String {
+        let (left, right) = source_texts(items);
+        let mut html = String::from(r#"
+            <table class="side-by-side">"#);
+        
+        for row in align_rows(items, &left, &right) {
+            let class = match row.kind {
+                DiffType::Add => "row-add",
+                DiffType::Remove => "row-remove",
+                DiffType::Modify => "row-modify",
+                DiffType::Equal => "row-equal",
+                DiffType::Move => "row-move",
+            };
+            
+            html.push_str(&format!(r#"
+                <tr class="{}">{}{}</tr>"#,
+                class,
+                self.render_row_cell_html(row.left.as_ref(), "left"),
+                self.render_row_cell_html(row.right.as_ref(), "right")
+            ));
+        }
+        
+        html.push_str(r#"
+            </table>"#);
+        html
+    }
+    
+    /// 并排表格的一侧：行号与带行内高亮的文本
+    fn render_row_cell_html(&self, cell: Option<&RowCell>, side: &str) -> String {
+        let Some(cell) = cell else {
+            return format!(r#"<td class="line-number filler"></td><td class="{} filler"></td>"#, side);
+        };
+        
+        let chars: Vec<char> = cell.text.chars().collect();
+        let escape = |from: usize, to: usize| {
+            html_escape::encode_text(&chars[from..to].iter().collect::<String>()).into_owned()
+        };
+        
+        // 与前一处重叠的高亮从前一处的末尾开始，完全被覆盖的跳过
+        let mut text = String::new();
+        let mut offset = 0;
+        for highlight in &cell.highlights {
+            let start = highlight.start.max(offset);
+            if start >= highlight.end {
+                continue;
+            }
+            let class = match highlight.diff_type {
+                DiffType::Add => "inline-add",
+                DiffType::Remove => "inline-remove",
+                DiffType::Move => "inline-move",
+                _ => "inline-modify",
+            };
+            text.push_str(&escape(offset, start));
+            text.push_str(&format!(r#"<span class="{}">{}</span>"#, class, escape(start, highlight.end)));
+            offset = highlight.end;
+        }
+        text.push_str(&escape(offset, chars.len()));
+        
+        format!(r#"<td class="line-number">{}</td><td class="{}">{}</td>"#, cell.line, side, text)
+    }
+    
+    /// 行级对比结果渲染为统一格式（unified diff），每块前后保留3行上下文
+    fn render_line_diff(&self, items: &[DiffItem]) -> String {
+        // 移动项的来源行按左侧位置输出，补丁可直接应用
+        let (left, right) = source_texts(items);
+        let result = DiffResult {
+            items: items.to_vec(),
+            stats: DiffStats::default(),
+            metadata: DiffMetadata::default(),
+        };
+        let options = PatchOptions {
+            context_lines: 3,
+            left_label: "左侧".to_string(),
+            right_label: "右侧".to_string(),
+        };
+        
+        result.to_unified_diff(&left, &right, &options)
+    }
+    
+    /// 用成对标记渲染修改项内部的差异（纯文本、Markdown）
+    fn render_inline_marked(
+        &self,
+        changes: &[DiffItem],
+        removed_marks: (&str, &str),
+        added_marks: (&str, &str),
+    ) -> String {
+        let mut text = String::new();
+        
+        for change in changes {
+            let original = change.original_content.as_deref().unwrap_or_default();
+            
+            match change.diff_type {
+                DiffType::Modify if !change.inline_changes.is_empty() => {
+                    text.push_str(&self.render_inline_marked(&change.inline_changes, removed_marks, added_marks));
+                }
+                DiffType::Equal => text.push_str(&change.content),
+                DiffType::Add | DiffType::Move => {
+                    text.push_str(&format!("{}{}{}", added_marks.0, change.content, added_marks.1));
+                }
+                DiffType::Remove => {
+                    text.push_str(&format!("{}{}{}", removed_marks.0, original, removed_marks.1));
+                }
+                DiffType::Modify => {
+                    text.push_str(&format!(
+                        "{}{}{}{}{}{}",
+                        removed_marks.0, original, removed_marks.1,
+                        added_marks.0, change.content, added_marks.1
+                    ));
+                }
+            }
+        }
+        
+        text
+    }
+    
+    /// 修改项内部差异对应的 DOCX 文本段
+    fn inline_docx_runs(&self, change: &DiffItem) -> Vec<docx_rs::Run> {
+        use docx_rs::Run;
+        
+        let original = change.original_content.as_deref().unwrap_or_default();
+        let removed = || Run::new().add_text(original).color("ef4444").strike();
+        let added = || Run::new().add_text(&change.content).color("3b82f6").underline("single");
+        
+        match change.diff_type {
+            DiffType::Modify if !change.inline_changes.is_empty() => change
+                .inline_changes
+                .iter()
+                .flat_map(|child| self.inline_docx_runs(child))
+                .collect(),
+            DiffType::Equal => vec![Run::new().add_text(&change.content)],
+            DiffType::Add | DiffType::Move => vec![added()],
+            DiffType::Remove => vec![removed()],
+            DiffType::Modify => vec![removed(), added()],
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,206 @@
+// 表格对比结果导出：高亮的 HTML 表格与 XLSX 工作簿
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use rust_xlsxwriter::{Color, Format, FormatBorder, Note, Workbook, XlsxError};
+
+use crate::diff_engine::{DiffType, TableCell, TableDiff};
+
+use super::{ExportError, ExportFormat, Exporter};
+
+impl From<XlsxError> for ExportError {
+    fn from(e: XlsxError) -> Self {
+        ExportError::ExportFailed(e.to_string())
+    }
+}
+
+impl Exporter {
+    /// 导出表格对比结果，支持 HTML、XLSX 与 JSON
+    pub async fn export_table(&self, diff: &TableDiff, output_path: &Path) -> Result<(), ExportError> {
+        match self.options.format {
+            ExportFormat::Html => {
+                let mut file = File::create(output_path)?;
+                file.write_all(self.render_table_html(diff).as_bytes())?;
+                Ok(())
+            }
+            ExportFormat::Xlsx => self.export_table_xlsx(diff, output_path),
+            ExportFormat::Json => {
+                let json = serde_json::to_string_pretty(diff).map_err(|e| ExportError::ExportFailed(e.to_string()))?;
+                let mut file = File::create(output_path)?;
+                file.write_all(json.as_bytes())?;
+                Ok(())
+            }
+            ref format => Err(ExportError::ExportFailed(format!("表格对比结果不支持导出为 {:?}", format))),
+        }
+    }
+
+    fn render_table_html(&self, diff: &TableDiff) -> String {
+        let styles = &self.options.styles;
+        let mut html = format!(
+            r#"<!DOCTYPE html>
+<html lang="zh-CN">
+<head>
+    <meta charset="UTF-8">
+    <title>表格对比报告</title>
+    <style>
+        body {{ font-family: {}; font-size: {}; color: #333; padding: 20px; }}
+        table {{ border-collapse: collapse; }}
+        th, td {{ border: 1px solid #d0d7de; padding: 4px 8px; white-space: pre-wrap; vertical-align: top; }}
+        th {{ background: #f6f8fa; }}
+        .stats {{ margin-bottom: 16px; color: #666; }}
+        .col-add {{ color: {}; }}
+        .col-remove {{ color: {}; text-decoration: line-through; }}
+        tr.row-add td {{ background: #e6ffed; }}
+        tr.row-remove td {{ background: #ffebe9; text-decoration: line-through; }}
+        td.cell-modify {{ background: #fff8c5; }}
+        td.cell-add {{ background: #e6ffed; }}
+        td.cell-remove {{ background: #ffebe9; }}
+        del {{ color: {}; }}
+        ins {{ color: {}; text-decoration: none; }}
+    </style>
+</head>
+<body>
+    <h1>表格对比报告</h1>"#,
+            styles.font_family, styles.font_size, styles.add_color, styles.remove_color, styles.remove_color, styles.add_color
+        );
+
+        if self.options.include_timestamp {
+            html.push_str(&format!(
+                r#"
+    <p class="stats">生成时间: {}</p>"#,
+                chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+            ));
+        }
+
+        if self.options.include_stats {
+            let stats = &diff.stats;
+            html.push_str(&format!(
+                r#"
+    <p class="stats">新增 {} 行，删除 {} 行，修改 {} 行（{} 个单元格），未变 {} 行；新增 {} 列，删除 {} 列</p>"#,
+                stats.added_rows,
+                stats.removed_rows,
+                stats.modified_rows,
+                stats.changed_cells,
+                stats.unchanged_rows,
+                stats.added_columns,
+                stats.removed_columns
+            ));
+        }
+
+        html.push_str("\n    <table>\n        <tr>");
+        for column in &diff.columns {
+            let class = match column.diff_type {
+                DiffType::Add => r#" class="col-add""#,
+                DiffType::Remove => r#" class="col-remove""#,
+                _ => "",
+            };
+            html.push_str(&format!("<th{}>{}</th>", class, html_escape::encode_text(&column.name)));
+        }
+        html.push_str("</tr>");
+
+        for row in &diff.rows {
+            let class = match row.diff_type {
+                DiffType::Add => "row-add",
+                DiffType::Remove => "row-remove",
+                DiffType::Modify => "row-modify",
+                _ => "row-equal",
+            };
+            html.push_str(&format!("\n        <tr class=\"{}\">", class));
+            for cell in &row.cells {
+                html.push_str(&render_cell_html(cell));
+            }
+            html.push_str("</tr>");
+        }
+
+        html.push_str("\n    </table>\n</body>\n</html>");
+        html
+    }
+
+    /// 表头与新增列为绿色，删除的行列为红色删除线，修改的单元格为黄色并以批注保留原值
+    fn export_table_xlsx(&self, diff: &TableDiff, output_path: &Path) -> Result<(), ExportError> {
+        let mut workbook = Workbook::new();
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name("表格对比")?;
+
+        let base = Format::new().set_border(FormatBorder::Thin);
+        let header = base.clone().set_bold().set_background_color(Color::RGB(0xF6F8FA));
+        let added = base.clone().set_background_color(Color::RGB(0xE6FFED));
+        let removed = base.clone().set_background_color(Color::RGB(0xFFEBE9)).set_font_strikethrough();
+        let modified = base.clone().set_background_color(Color::RGB(0xFFF8C5));
+
+        // 第一列标注行的变化
+        worksheet.write_string_with_format(0, 0, "变化", &header)?;
+        for (c, column) in diff.columns.iter().enumerate() {
+            let format = match column.diff_type {
+                DiffType::Add => &added,
+                DiffType::Remove => &removed,
+                _ => &header,
+            };
+            worksheet.write_string_with_format(0, sheet_column(c)?, &column.name, format)?;
+        }
+
+        for (r, row) in diff.rows.iter().enumerate() {
+            let r = sheet_row(r)?;
+            let label = match row.diff_type {
+                DiffType::Add => "新增",
+                DiffType::Remove => "删除",
+                DiffType::Modify => "修改",
+                _ => "",
+            };
+            worksheet.write_string_with_format(r, 0, label, &base)?;
+
+            for (c, cell) in row.cells.iter().enumerate() {
+                let c = sheet_column(c)?;
+                let text = cell.right.as_deref().or(cell.left.as_deref()).unwrap_or_default();
+                let format = match (row.diff_type, cell.diff_type) {
+                    (DiffType::Remove, _) | (_, DiffType::Remove) => &removed,
+                    (DiffType::Add, _) | (_, DiffType::Add) => &added,
+                    (_, DiffType::Modify) => &modified,
+                    _ => &base,
+                };
+                worksheet.write_string_with_format(r, c, text, format)?;
+
+                if cell.diff_type == DiffType::Modify {
+                    let original = cell.left.as_deref().unwrap_or_default();
+                    worksheet.insert_note(r, c, &Note::new(format!("原值: {}", original)))?;
+                }
+            }
+        }
+
+        worksheet.set_freeze_panes(1, 0)?;
+        workbook.save(output_path)?;
+        Ok(())
+    }
+}
+
+/// 修改的单元格同时显示原值与新值
+fn render_cell_html(cell: &TableCell) -> String {
+    let escape = |value: &Option<String>| html_escape::encode_text(value.as_deref().unwrap_or_default()).into_owned();
+    match cell.diff_type {
+        DiffType::Modify => format!(
+            r#"<td class="cell-modify"><del>{}</del><br><ins>{}</ins></td>"#,
+            escape(&cell.left),
+            escape(&cell.right)
+        ),
+        DiffType::Add => format!(r#"<td class="cell-add">{}</td>"#, escape(&cell.right)),
+        DiffType::Remove => format!(r#"<td class="cell-remove">{}</td>"#, escape(&cell.left)),
+        _ => format!("<td>{}</td>", escape(&cell.right)),
+    }
+}
+
+/// 数据行下标 → 工作表行号（首行为表头）
+fn sheet_row(index: usize) -> Result<u32, ExportError> {
+    index
+        .checked_add(1)
+        .and_then(|row| u32::try_from(row).ok())
+        .ok_or_else(|| ExportError::ExportFailed(format!("行数超出工作表上限: {}", index)))
+}
+
+/// 数据列下标 → 工作表列号（首列标注行的变化）
+fn sheet_column(index: usize) -> Result<u16, ExportError> {
+    index
+        .checked_add(1)
+        .and_then(|column| u16::try_from(column).ok())
+        .ok_or_else(|| ExportError::ExportFailed(format!("列数超出工作表上限: {}", index)))
+}
@@ -4,6 +4,9 @@ use std::io::Read;
 use std::path::Path;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use crate::diff_engine::Table;
+
+mod table;
 
 #[derive(Error, Debug)]
 pub enum ParseError {
@@ -37,6 +40,8 @@ pub enum DocumentFormat {
     Rtf,
     Html,
     Markdown,
+    Csv,
+    Xlsx,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,6 +71,20 @@ pub enum StyleType {
     Link(String),
 }
 
+/// 段落分隔符：对比引擎按空行切分段落
+pub const PARAGRAPH_SEPARATOR: &str = "\n\n";
+
+/// 视为段落边界的 HTML 块级元素
+const HTML_BLOCK_ELEMENTS: &[&str] = &[
+    "p", "div", "section", "article", "header", "footer", "blockquote", "pre",
+    "h1", "h2", "h3", "h4", "h5", "h6", "li", "tr", "table", "ul", "ol", "br",
+];
+
+/// 是否为视为段落边界的块级元素
+fn is_html_block(node: &scraper::Node) -> bool {
+    matches!(node, scraper::Node::Element(element) if HTML_BLOCK_ELEMENTS.contains(&element.name()))
+}
+
 /// 文件解析器主接口
 pub struct FileParser {
     max_file_size_mb: usize,
@@ -86,20 +105,10 @@ impl FileParser {
     
     /// 解析文件
     pub async fn parse_file(&self, file_path: &Path) -> Result<ParsedDocument, ParseError> {
-        // 检查文件大小
-        let metadata = std::fs::metadata(file_path)?;
-        let file_size_mb = metadata.len() as usize / (1024 * 1024);
-        
-        if file_size_mb > self.max_file_size_mb {
-            return Err(ParseError::FileTooLarge(file_size_mb, self.max_file_size_mb));
-        }
+        self.check_size(file_path)?;
         
         // 根据扩展名选择解析器
-        let extension = file_path
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .unwrap_or("")
-            .to_lowercase();
+        let extension = self.extension(file_path);
         
         match extension.as_str() {
             "txt" | "text" => self.parse_text_file(file_path).await,
@@ -109,10 +118,68 @@ impl FileParser {
             "rtf" => self.parse_rtf_file(file_path).await,
             "html" | "htm" => self.parse_html_file(file_path).await,
             "md" | "markdown" => self.parse_markdown_file(file_path).await,
+            "csv" | "tsv" | "xlsx" | "xlsm" => self.parse_table_file(file_path).await,
             _ => Err(ParseError::UnsupportedFormat(extension)),
         }
     }
     
+    /// 解析表格文件（CSV、TSV、XLSX）为表格模型，XLSX 取第一张工作表
+    pub async fn parse_table(&self, file_path: &Path) -> Result<Table, ParseError> {
+        self.check_size(file_path)?;
+        
+        let extension = self.extension(file_path);
+        match extension.as_str() {
+            "csv" => table::parse_delimited(&std::fs::read_to_string(file_path)?, b','),
+            "tsv" => table::parse_delimited(&std::fs::read_to_string(file_path)?, b'\t'),
+            "xlsx" | "xlsm" => table::parse_xlsx(file_path, None),
+            _ => Err(ParseError::UnsupportedFormat(extension)),
+        }
+    }
+    
+    fn check_size(&self, file_path: &Path) -> Result<(), ParseError> {
+        let metadata = std::fs::metadata(file_path)?;
+        let file_size_mb = metadata.len() as usize / (1024 * 1024);
+        
+        if file_size_mb > self.max_file_size_mb {
+            return Err(ParseError::FileTooLarge(file_size_mb, self.max_file_size_mb));
+        }
+        Ok(())
+    }
+    
+    fn extension(&self, file_path: &Path) -> String {
+        file_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or("")
+            .to_lowercase()
+    }
+    
+    /// 解析表格文件为文本，每行一条记录，单元格以制表符分隔
+    async fn parse_table_file(&self, file_path: &Path) -> Result<ParsedDocument, ParseError> {
+        let table = self.parse_table(file_path).await?;
+        let content = table::table_text(&table);
+        let word_count = content.split_whitespace().count();
+        let format = if self.extension(file_path).starts_with("xls") {
+            DocumentFormat::Xlsx
+        } else {
+            DocumentFormat::Csv
+        };
+        
+        Ok(ParsedDocument {
+            format,
+            content,
+            metadata: DocumentMetadata {
+                title: file_path.file_name().and_then(|n| n.to_str()).map(String::from),
+                author: None,
+                created_date: None,
+                modified_date: None,
+                word_count,
+                page_count: None,
+            },
+            styles: None,
+        })
+    }
+    
     /// 解析纯文本文件
     async fn parse_text_file(&self, file_path: &Path) -> Result<ParsedDocument, ParseError> {
         let mut file = File::open(file_path)?;
@@ -155,6 +222,12 @@ impl FileParser {
         for child in docx.document.children.iter() {
             match child {
                 docx_rs::DocumentChild::Paragraph(p) => {
+                    // 段落之间以空行分隔
+                    if !content.is_empty() {
+                        content.push_str(PARAGRAPH_SEPARATOR);
+                        current_pos += PARAGRAPH_SEPARATOR.len();
+                    }
+                    
                     for run in p.children.iter() {
                         if let docx_rs::ParagraphChild::Run(r) = run {
                             for text_child in r.children.iter() {
@@ -193,8 +266,6 @@ impl FileParser {
                             }
                         }
                     }
-                    content.push('\n');
-                    current_pos += 1;
                 }
                 _ => {}
             }
@@ -337,7 +408,8 @@ impl FileParser {
     
     /// 解析HTML文件
     async fn parse_html_file(&self, file_path: &Path) -> Result<ParsedDocument, ParseError> {
-        use scraper::{Html, Selector};
+        use ego_tree::iter::Edge;
+        use scraper::{Html, Node, Selector};
         
         let mut file = File::open(file_path)?;
         let mut html_content = String::new();
@@ -350,7 +422,29 @@ impl FileParser {
         let mut content = String::new();
         
         if let Some(body) = document.select(&body_selector).next() {
-            content = body.text().collect::<Vec<_>>().join(" ");
+            // 块级元素的开始与结束处以空行分隔，同一块内的文本以空格连接
+            let mut block_boundary = false;
+            for edge in body.traverse() {
+                match edge {
+                    Edge::Open(node) | Edge::Close(node) if is_html_block(node.value()) => {
+                        block_boundary = true;
+                    }
+                    Edge::Open(node) => {
+                        if let Node::Text(text) = node.value() {
+                            let text = text.trim();
+                            if text.is_empty() {
+                                continue;
+                            }
+                            if !content.is_empty() {
+                                content.push_str(if block_boundary { PARAGRAPH_SEPARATOR } else { " " });
+                            }
+                            content.push_str(text);
+                            block_boundary = false;
+                        }
+                    }
+                    Edge::Close(_) => {}
+                }
+            }
         }
         
         // 提取标题
@@ -0,0 +1,127 @@
+// 表格文件解析：CSV、TSV 与 XLSX 解析为表格模型，首行为表头
+use std::path::Path;
+
+use crate::diff_engine::Table;
+
+use super::ParseError;
+
+/// 解析分隔符文本（CSV 用 `,`，TSV 用 `\t`），允许各行列数不同
+pub fn parse_delimited(text: &str, delimiter: u8) -> Result<Table, ParseError> {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+
+    let mut rows = reader
+        .records()
+        .map(|record| {
+            record
+                .map(|record| record.iter().map(String::from).collect())
+                .map_err(|e| ParseError::ParseFailed(e.to_string()))
+        })
+        .collect::<Result<Vec<Vec<String>>, _>>()?
+        .into_iter();
+
+    Ok(Table {
+        headers: rows.next().unwrap_or_default(),
+        rows: rows.collect(),
+    })
+}
+
+/// 解析 XLSX 工作表，未指定工作表时取第一张；数字与日期按显示文本转为字符串
+pub fn parse_xlsx(file_path: &Path, sheet: Option<&str>) -> Result<Table, ParseError> {
+    use calamine::{open_workbook, Reader, Xlsx, XlsxError};
+
+    let mut workbook: Xlsx<_> = open_workbook(file_path).map_err(|e: XlsxError| ParseError::ParseFailed(e.to_string()))?;
+    let sheet = match sheet {
+        Some(sheet) => sheet.to_string(),
+        None => workbook
+            .sheet_names()
+            .first()
+            .cloned()
+            .ok_or_else(|| ParseError::ParseFailed("工作簿中没有工作表".to_string()))?,
+    };
+
+    let range = workbook
+        .worksheet_range(&sheet)
+        .map_err(|e| ParseError::ParseFailed(format!("{}: {}", sheet, e)))?;
+    let mut rows = range.rows().map(|row| row.iter().map(cell_text).collect::<Vec<_>>());
+
+    Ok(Table {
+        headers: rows.next().unwrap_or_default(),
+        rows: rows.collect(),
+    })
+}
+
+/// 单元格的显示文本：日期时间按日期格式输出（calamine 的文本形式为序列值），其余沿用 calamine 的文本形式
+fn cell_text(cell: &calamine::Data) -> String {
+    use calamine::Data;
+
+    match cell {
+        Data::DateTime(value) if value.is_datetime() => match value.as_datetime() {
+            // 小于 1 的序列值只有时间部分
+            Some(datetime) if value.as_f64() < 1.0 => datetime.format("%H:%M:%S").to_string(),
+            Some(datetime) if datetime.time() == chrono::NaiveTime::MIN => datetime.format("%Y-%m-%d").to_string(),
+            Some(datetime) => datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+            None => cell.to_string(),
+        },
+        Data::DateTime(value) => match value.as_duration() {
+            Some(duration) => {
+                let seconds = duration.num_seconds();
+                format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+            }
+            None => cell.to_string(),
+        },
+        _ => cell.to_string(),
+    }
+}
+
+/// 表格按行转为文本，单元格以制表符分隔，供普通文本对比使用
+pub fn table_text(table: &Table) -> String {
+    std::iter::once(&table.headers)
+        .chain(&table.rows)
+        .map(|row| row.join("\t") + "\n")
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_quoted_csv_and_tsv() {
+        let table = parse_delimited("\u{feff}编号,名称,备注\n1,\"苹果, 红富士\",\"第一行\n第二行\"\n2,香蕉\n", b',').unwrap();
+        assert_eq!(table.headers, vec!["编号", "名称", "备注"]);
+        assert_eq!(table.rows[0], vec!["1", "苹果, 红富士", "第一行\n第二行"]);
+        assert_eq!(table.cell(1, 2), "");
+
+        let table = parse_delimited("a\tb\n1\t2\n", b'\t').unwrap();
+        assert_eq!(table_text(&table), "a\tb\n1\t2\n");
+    }
+
+    #[test]
+    fn test_xlsx_dates_are_read_as_dates() {
+        use rust_xlsxwriter::{ExcelDateTime, Format, Workbook};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dates.xlsx");
+
+        let mut workbook = Workbook::new();
+        let worksheet = workbook.add_worksheet();
+        worksheet.write_string(0, 0, "日期").unwrap();
+        worksheet.write_string(0, 1, "时间").unwrap();
+        worksheet.write_string(0, 2, "金额").unwrap();
+        let date = ExcelDateTime::from_ymd(2024, 1, 1).unwrap();
+        worksheet.write_datetime_with_format(1, 0, &date, &Format::new().set_num_format("yyyy-mm-dd")).unwrap();
+        let datetime = ExcelDateTime::from_ymd(2024, 1, 1).unwrap().and_hms(12, 30, 0).unwrap();
+        worksheet.write_datetime_with_format(1, 1, &datetime, &Format::new().set_num_format("yyyy-mm-dd hh:mm")).unwrap();
+        worksheet.write_number(1, 2, 12.5).unwrap();
+        workbook.save(&path).unwrap();
+
+        let table = parse_xlsx(&path, None).unwrap();
+        assert_eq!(table.headers, vec!["日期", "时间", "金额"]);
+        assert_eq!(table.rows[0], vec!["2024-01-01", "2024-01-01 12:30:00", "12.5"]);
+    }
+}
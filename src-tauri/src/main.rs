@@ -8,11 +8,16 @@ mod plugin_system;
 mod database;
 mod security;
 
+use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Mutex;
 use tauri::{Manager, State};
 use serde_json::Value;
 
-use diff_engine::{DiffEngine, DiffOptions};
+use diff_engine::{
+    CancellationToken, CodeLanguage, DiffAlgorithm, DiffEngine, DiffError, DiffOptions, DiffProgress,
+    PatchOptions, ReviewDecision, ReviewSession, RuleProfile, Table, TableDiff,
+};
 use file_parser::FileParser;
 use exporter::{Exporter, ExportOptions, ExportFormat};
 
@@ -20,6 +25,70 @@ use exporter::{Exporter, ExportOptions, ExportFormat};
 struct AppState {
     diff_engine: DiffEngine,
     file_parser: FileParser,
+    jobs: Jobs,
+}
+
+// 正在运行、可取消的对比任务
+#[derive(Default)]
+struct Jobs(Mutex<HashMap<String, CancellationToken>>);
+
+impl Jobs {
+    // 同一编号同时只能有一个任务，否则先注册的任务将无法取消
+    fn register(&self, job_id: &str) -> Result<CancellationToken, String> {
+        let mut jobs = self.0.lock().unwrap();
+        if jobs.contains_key(job_id) {
+            return Err(format!("任务编号已在使用: {}", job_id));
+        }
+        
+        let token = CancellationToken::new();
+        jobs.insert(job_id.to_string(), token.clone());
+        Ok(token)
+    }
+    
+    fn remove(&self, job_id: &str) {
+        self.0.lock().unwrap().remove(job_id);
+    }
+    
+    fn cancel(&self, job_id: &str) -> bool {
+        match self.0.lock().unwrap().get(job_id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+// 对比进度事件
+#[derive(Clone, serde::Serialize)]
+struct JobProgress {
+    job_id: String,
+    file_index: usize,
+    file_count: usize,
+    processed: usize,
+    total: usize,
+}
+
+// 把引擎的进度回调转发为前端事件
+fn emit_progress(
+    window: &tauri::Window,
+    job_id: &str,
+    file_index: usize,
+    file_count: usize,
+) -> impl Fn(DiffProgress) + Send + Sync + 'static {
+    let window = window.clone();
+    let job_id = job_id.to_string();
+    
+    move |progress| {
+        let _ = window.emit("diff-progress", JobProgress {
+            job_id: job_id.clone(),
+            file_index,
+            file_count,
+            processed: progress.processed,
+            total: progress.total,
+        });
+    }
 }
 
 // Tauri命令
@@ -29,15 +98,329 @@ async fn compute_diff(
     left_text: String,
     right_text: String,
     options: DiffOptions,
+    job_id: Option<String>,
+    window: tauri::Window,
     state: State<'_, AppState>,
+) -> Result<Value, String> {
+    let mut engine = DiffEngine::new(options);
+    
+    // 指定任务编号时可通过 cancel_job 取消，并通过 diff-progress 事件报告进度
+    if let Some(job_id) = &job_id {
+        engine = engine
+            .with_cancellation(state.jobs.register(job_id)?)
+            .with_progress(emit_progress(&window, job_id, 0, 1));
+    }
+    
+    let result = tokio::task::spawn_blocking(move || engine.try_compute_diff(&left_text, &right_text)).await;
+    
+    if let Some(job_id) = &job_id {
+        state.jobs.remove(job_id);
+    }
+    
+    let result = result
+        .map_err(|e| format!("对比任务失败: {}", e))?
+        .map_err(|e| e.to_string())?;
+    
+    serde_json::to_value(&result)
+        .map_err(|e| format!("序列化失败: {}", e))
+}
+
+#[tauri::command]
+async fn cancel_job(job_id: String, state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.jobs.cancel(&job_id))
+}
+
+#[tauri::command]
+async fn compare_algorithms(
+    left_text: String,
+    right_text: String,
+    options: DiffOptions,
+    algorithms: Vec<DiffAlgorithm>,
+) -> Result<Vec<Value>, String> {
+    // 同一组输入分别用多种算法计算，便于并排比较结果
+    let results = tokio::task::spawn_blocking(move || {
+        algorithms
+            .into_iter()
+            .map(|algorithm| {
+                let engine = DiffEngine::new(DiffOptions {
+                    algorithm: Some(algorithm),
+                    ..options.clone()
+                });
+                engine.compute_diff(&left_text, &right_text)
+            })
+            .collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| format!("对比任务失败: {}", e))?;
+    
+    results
+        .iter()
+        .map(|result| serde_json::to_value(result).map_err(|e| format!("序列化失败: {}", e)))
+        .collect()
+}
+
+// 流式对比的默认分块行数
+const STREAM_CHUNK_LINES: usize = 500;
+
+#[tauri::command]
+async fn compute_side_by_side(
+    left_text: String,
+    right_text: String,
+    options: DiffOptions,
 ) -> Result<Value, String> {
     let engine = DiffEngine::new(options);
-    let result = engine.compute_diff(&left_text, &right_text);
+    let view = tokio::task::spawn_blocking(move || engine.compute_side_by_side(&left_text, &right_text))
+        .await
+        .map_err(|e| format!("对比任务失败: {}", e))?;
+    
+    serde_json::to_value(&view)
+        .map_err(|e| format!("序列化失败: {}", e))
+}
+
+#[tauri::command]
+async fn compute_diff_stream(
+    window: tauri::Window,
+    left_text: String,
+    right_text: String,
+    options: DiffOptions,
+    chunk_size: Option<usize>,
+    job_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Value, String> {
+    let token = job_id.as_deref().map(|job_id| state.jobs.register(job_id)).transpose()?;
+    let mut engine = DiffEngine::new(options);
+    
+    // 指定任务编号时可通过 cancel_job 取消
+    if let Some(token) = &token {
+        engine = engine.with_cancellation(token.clone());
+    }
+    
+    // 在阻塞线程中逐块对比，每算完一块即通过事件发给前端，命令返回整体统计
+    let stats = tokio::task::spawn_blocking(move || {
+        let mut stream = engine.diff_chunks(&left_text, &right_text, chunk_size.unwrap_or(STREAM_CHUNK_LINES));
+        
+        for chunk in stream.by_ref() {
+            window.emit("diff-chunk", &chunk)
+                .map_err(|e| format!("发送差异块失败: {}", e))?;
+        }
+        
+        if token.as_ref().is_some_and(CancellationToken::is_cancelled) {
+            return Err(DiffError::Cancelled.to_string());
+        }
+        
+        Ok(stream.stats())
+    })
+    .await;
+    
+    if let Some(job_id) = &job_id {
+        state.jobs.remove(job_id);
+    }
+    
+    let stats = stats.map_err(|e| format!("对比任务失败: {}", e))??;
+    
+    serde_json::to_value(&stats)
+        .map_err(|e| format!("序列化失败: {}", e))
+}
+
+#[tauri::command]
+async fn compute_diff3(
+    base_text: String,
+    left_text: String,
+    right_text: String,
+    options: DiffOptions,
+) -> Result<Value, String> {
+    let engine = DiffEngine::new(options);
+    let result = tokio::task::spawn_blocking(move || engine.compute_diff3(&base_text, &left_text, &right_text))
+        .await
+        .map_err(|e| format!("对比任务失败: {}", e))?;
+    
+    serde_json::to_value(&result)
+        .map_err(|e| format!("序列化失败: {}", e))
+}
+
+#[tauri::command]
+async fn merge_texts(
+    base_text: String,
+    left_text: String,
+    right_text: String,
+    options: DiffOptions,
+) -> Result<Value, String> {
+    let engine = DiffEngine::new(options);
+    let result = tokio::task::spawn_blocking(move || engine.merge3(&base_text, &left_text, &right_text))
+        .await
+        .map_err(|e| format!("对比任务失败: {}", e))?;
     
     serde_json::to_value(&result)
         .map_err(|e| format!("序列化失败: {}", e))
 }
 
+#[tauri::command]
+async fn create_patch(
+    left_text: String,
+    right_text: String,
+    options: DiffOptions,
+    patch_options: Option<PatchOptions>,
+) -> Result<String, String> {
+    let engine = DiffEngine::new(options);
+    tokio::task::spawn_blocking(move || {
+        let result = engine.compute_diff(&left_text, &right_text);
+        result.to_unified_diff(&left_text, &right_text, &patch_options.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| format!("对比任务失败: {}", e))
+}
+
+#[tauri::command]
+async fn apply_patch(text: String, patch: String) -> Result<Value, String> {
+    // 返回应用后的文本以及各补丁块的偏移量与模糊匹配行数
+    let applied = tokio::task::spawn_blocking(move || diff_engine::apply_patch_detailed(&text, &patch))
+        .await
+        .map_err(|e| format!("对比任务失败: {}", e))?
+        .map_err(|e| format!("应用补丁失败: {}", e))?;
+    
+    serde_json::to_value(&applied)
+        .map_err(|e| format!("序列化失败: {}", e))
+}
+
+#[tauri::command]
+async fn start_review(
+    left_text: String,
+    right_text: String,
+    options: DiffOptions,
+) -> Result<Value, String> {
+    let engine = DiffEngine::new(options);
+    let session = tokio::task::spawn_blocking(move || {
+        let result = engine.compute_diff(&left_text, &right_text);
+        ReviewSession::new(&left_text, &right_text, result)
+    })
+    .await
+    .map_err(|e| format!("对比任务失败: {}", e))?;
+    
+    // 原文与对比结果只在创建时保存一次，之后的决定单独保存
+    let review_id = database::create_review(&review_to_value(&session)?)
+        .map_err(|e| format!("保存审阅失败: {}", e))?;
+    
+    review_with_session(review_id, &session)
+}
+
+#[tauri::command]
+async fn load_review(review_id: i64) -> Result<Value, String> {
+    let session = load_review_session(review_id)?;
+    review_with_session(review_id, &session)
+}
+
+#[tauri::command]
+async fn set_review_decision(
+    review_id: i64,
+    item_id: String,
+    decision: ReviewDecision,
+) -> Result<Value, String> {
+    let mut session = load_review_session(review_id)?;
+    session.decide(&item_id, decision)
+        .map_err(|e| format!("审阅失败: {}", e))?;
+    
+    database::set_review_decision(review_id, &item_id, decision_name(session.decision(&item_id)))
+        .map_err(|e| format!("保存审阅失败: {}", e))?;
+    Ok(review_summary(review_id, &session))
+}
+
+#[tauri::command]
+async fn set_all_review_decisions(
+    review_id: i64,
+    decision: ReviewDecision,
+) -> Result<Value, String> {
+    let mut session = load_review_session(review_id)?;
+    session.decide_all(decision);
+    
+    let decisions: Vec<(&str, &str)> = session
+        .decisions
+        .iter()
+        .filter_map(|(item_id, &decision)| Some((item_id.as_str(), decision_name(decision)?)))
+        .collect();
+    database::replace_review_decisions(review_id, &decisions)
+        .map_err(|e| format!("保存审阅失败: {}", e))?;
+    Ok(review_summary(review_id, &session))
+}
+
+#[tauri::command]
+async fn render_review(review_id: i64) -> Result<String, String> {
+    let session = load_review_session(review_id)?;
+    Ok(session.render())
+}
+
+fn review_to_value(session: &ReviewSession) -> Result<Value, String> {
+    serde_json::to_value(session)
+        .map_err(|e| format!("序列化失败: {}", e))
+}
+
+// 决定在数据库中的名称，未决的不保存
+fn decision_name(decision: ReviewDecision) -> Option<&'static str> {
+    match decision {
+        ReviewDecision::Pending => None,
+        ReviewDecision::Accepted => Some("accepted"),
+        ReviewDecision::Rejected => Some("rejected"),
+    }
+}
+
+// 加载审阅会话，并应用单独保存的决定
+fn load_review_session(review_id: i64) -> Result<ReviewSession, String> {
+    let value = database::load_review(review_id)
+        .map_err(|e| format!("加载审阅失败: {}", e))?
+        .ok_or_else(|| format!("审阅不存在: {}", review_id))?;
+    
+    let mut session: ReviewSession = serde_json::from_value(value)
+        .map_err(|e| format!("反序列化失败: {}", e))?;
+    
+    let decisions = database::load_review_decisions(review_id)
+        .map_err(|e| format!("加载审阅失败: {}", e))?;
+    for (item_id, name) in decisions {
+        let decision = match name.as_str() {
+            "accepted" => ReviewDecision::Accepted,
+            "rejected" => ReviewDecision::Rejected,
+            _ => continue,
+        };
+        session.decisions.insert(item_id, decision);
+    }
+    
+    Ok(session)
+}
+
+// 审阅概要：各项决定、进度以及下一个待审阅的差异项
+fn review_summary(review_id: i64, session: &ReviewSession) -> Value {
+    serde_json::json!({
+        "review_id": review_id,
+        "decisions": session.decisions,
+        "progress": session.progress(),
+        "next_pending": session.next_pending().map(|item| &item.id),
+    })
+}
+
+// 开始或继续审阅时连同会话本身（原文与对比结果）一并返回
+fn review_with_session(review_id: i64, session: &ReviewSession) -> Result<Value, String> {
+    let mut summary = review_summary(review_id, session);
+    summary["session"] = review_to_value(session)?;
+    Ok(summary)
+}
+
+// 规则配置以 JSON 文件保存，便于在团队间分享
+#[tauri::command]
+async fn save_rule_profile(profile: RuleProfile, path: String) -> Result<(), String> {
+    profile.validate().map_err(|e| e.to_string())?;
+    
+    tokio::fs::write(&path, profile.to_json()).await
+        .map_err(|e| format!("保存规则配置失败: {}", e))
+}
+
+#[tauri::command]
+async fn load_rule_profile(path: String) -> Result<Value, String> {
+    let json = tokio::fs::read_to_string(&path).await
+        .map_err(|e| format!("读取规则配置失败: {}", e))?;
+    let profile = RuleProfile::from_json(&json).map_err(|e| e.to_string())?;
+    
+    serde_json::to_value(&profile)
+        .map_err(|e| format!("序列化失败: {}", e))
+}
+
 #[tauri::command]
 async fn parse_file(
     file_path: String,
@@ -51,11 +434,72 @@ async fn parse_file(
     }
 }
 
+#[tauri::command]
+async fn parse_table(
+    file_path: String,
+    state: State<'_, AppState>,
+) -> Result<Value, String> {
+    let path = Path::new(&file_path);
+    
+    let table = state.file_parser.parse_table(path).await
+        .map_err(|e| format!("表格解析失败: {}", e))?;
+    
+    serde_json::to_value(&table)
+        .map_err(|e| format!("序列化失败: {}", e))
+}
+
+#[tauri::command]
+async fn compare_tables(
+    left: Table,
+    right: Table,
+    key_column: Option<String>,
+    options: DiffOptions,
+) -> Result<Value, String> {
+    let engine = DiffEngine::new(options);
+    let diff = tokio::task::spawn_blocking(move || engine.compute_table_diff(&left, &right, key_column.as_deref()))
+        .await
+        .map_err(|e| format!("对比任务失败: {}", e))?
+        .map_err(|e| e.to_string())?;
+    
+    serde_json::to_value(&diff)
+        .map_err(|e| format!("序列化失败: {}", e))
+}
+
+#[tauri::command]
+async fn export_table_diff(
+    table_diff: TableDiff,
+    output_path: String,
+    format: String,
+) -> Result<(), String> {
+    let export_format = match format.as_str() {
+        "html" => ExportFormat::Html,
+        "xlsx" => ExportFormat::Xlsx,
+        "json" => ExportFormat::Json,
+        _ => return Err(format!("表格对比结果不支持导出为: {}", format)),
+    };
+    
+    let exporter = Exporter::new(ExportOptions {
+        format: export_format,
+        include_stats: true,
+        include_timestamp: true,
+        include_metadata: true,
+        template: None,
+        styles: Default::default(),
+        side_by_side: false,
+        code_language: None,
+    });
+    
+    exporter.export_table(&table_diff, Path::new(&output_path)).await
+        .map_err(|e| format!("导出失败: {}", e))
+}
+
 #[tauri::command]
 async fn export_diff(
     diff_result: Value,
     output_path: String,
     format: String,
+    side_by_side: Option<bool>,
+    code_language: Option<CodeLanguage>,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
     let export_format = match format.as_str() {
@@ -75,6 +519,8 @@ async fn export_diff(
         include_metadata: true,
         template: None,
         styles: Default::default(),
+        side_by_side: side_by_side.unwrap_or(false),
+        code_language,
     };
     
     let exporter = Exporter::new(options);
@@ -98,29 +544,63 @@ async fn export_diff(
 async fn batch_compare(
     file_pairs: Vec<(String, String)>,
     options: DiffOptions,
+    job_id: Option<String>,
+    window: tauri::Window,
+    state: State<'_, AppState>,
 ) -> Result<Vec<Value>, String> {
-    let mut results = Vec::new();
-    let engine = DiffEngine::new(options);
+    let token = job_id.as_deref().map(|job_id| state.jobs.register(job_id)).transpose()?;
+    let file_count = file_pairs.len();
     
-    for (left_path, right_path) in file_pairs {
-        let parser = FileParser::new();
-        
-        // 解析文件
-        let left_doc = parser.parse_file(Path::new(&left_path)).await
-            .map_err(|e| format!("解析左侧文件失败: {}", e))?;
-        let right_doc = parser.parse_file(Path::new(&right_path)).await
-            .map_err(|e| format!("解析右侧文件失败: {}", e))?;
-        
-        // 计算差异
-        let result = engine.compute_diff(&left_doc.content, &right_doc.content);
+    let results = async {
+        let mut results = Vec::new();
         
-        let value = serde_json::to_value(&result)
-            .map_err(|e| format!("序列化失败: {}", e))?;
+        for (file_index, (left_path, right_path)) in file_pairs.into_iter().enumerate() {
+            if token.as_ref().is_some_and(CancellationToken::is_cancelled) {
+                return Err(DiffError::Cancelled.to_string());
+            }
+            
+            let parser = FileParser::new();
+            
+            // 解析文件
+            let left_doc = parser.parse_file(Path::new(&left_path)).await
+                .map_err(|e| format!("解析左侧文件失败: {}", e))?;
+            let right_doc = parser.parse_file(Path::new(&right_path)).await
+                .map_err(|e| format!("解析右侧文件失败: {}", e))?;
+            
+            // 批量对比的文档通常较大，按锚点分区并行对比
+            let mut engine = DiffEngine::new(DiffOptions {
+                parallel: true,
+                ..options.clone()
+            });
+            if let (Some(job_id), Some(token)) = (&job_id, &token) {
+                engine = engine
+                    .with_cancellation(token.clone())
+                    .with_progress(emit_progress(&window, job_id, file_index, file_count));
+            }
+            
+            // 计算差异
+            let result = tokio::task::spawn_blocking(move || {
+                engine.try_compute_diff(&left_doc.content, &right_doc.content)
+            })
+            .await
+            .map_err(|e| format!("对比任务失败: {}", e))?
+            .map_err(|e| e.to_string())?;
+            
+            let value = serde_json::to_value(&result)
+                .map_err(|e| format!("序列化失败: {}", e))?;
+            
+            results.push(value);
+        }
         
-        results.push(value);
+        Ok(results)
     }
+    .await;
     
-    Ok(results)
+    if let Some(job_id) = &job_id {
+        state.jobs.remove(job_id);
+    }
+    
+    results
 }
 
 #[tauri::command]
@@ -189,10 +669,29 @@ fn main() {
         .manage(AppState {
             diff_engine: DiffEngine::new(DiffOptions::default()),
             file_parser: FileParser::new(),
+            jobs: Jobs::default(),
         })
         .invoke_handler(tauri::generate_handler![
             compute_diff,
+            cancel_job,
+            compare_algorithms,
+            compute_side_by_side,
+            compute_diff_stream,
+            compute_diff3,
+            merge_texts,
+            create_patch,
+            apply_patch,
+            start_review,
+            load_review,
+            set_review_decision,
+            set_all_review_decisions,
+            render_review,
+            save_rule_profile,
+            load_rule_profile,
             parse_file,
+            parse_table,
+            compare_tables,
+            export_table_diff,
             export_diff,
             batch_compare,
             load_plugin,
@@ -283,7 +782,7 @@ mod plugin_system {
 
 // 数据库模块
 mod database {
-    use rusqlite::{Connection, Result};
+    use rusqlite::{Connection, OptionalExtension, Result};
     use serde_json::Value;
     use std::sync::Mutex;
     
@@ -305,6 +804,27 @@ mod database {
             [],
         )?;
         
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS review_sessions (
+                id INTEGER PRIMARY KEY,
+                session TEXT NOT NULL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )",
+            [],
+        )?;
+        
+        // 审阅决定单独保存，每次决定只写一行
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS review_decisions (
+                review_id INTEGER NOT NULL,
+                item_id TEXT NOT NULL,
+                decision TEXT NOT NULL,
+                PRIMARY KEY (review_id, item_id)
+            )",
+            [],
+        )?;
+        
         *DB.lock().unwrap() = Some(conn);
         Ok(())
     }
@@ -336,6 +856,94 @@ mod database {
         
         Ok(results.collect::<Result<Vec<_>, _>>()?)
     }
+    
+    pub fn create_review(session: &Value) -> Result<i64> {
+        let conn = DB.lock().unwrap();
+        let conn = conn.as_ref().unwrap();
+        
+        conn.execute(
+            "INSERT INTO review_sessions (session) VALUES (?1)",
+            [&session.to_string()],
+        )?;
+        
+        Ok(conn.last_insert_rowid())
+    }
+    
+    pub fn set_review_decision(id: i64, item_id: &str, decision: Option<&str>) -> Result<()> {
+        let conn = DB.lock().unwrap();
+        let conn = conn.as_ref().unwrap();
+        
+        match decision {
+            Some(decision) => conn.execute(
+                "INSERT OR REPLACE INTO review_decisions (review_id, item_id, decision) VALUES (?1, ?2, ?3)",
+                rusqlite::params![id, item_id, decision],
+            )?,
+            None => conn.execute(
+                "DELETE FROM review_decisions WHERE review_id = ?1 AND item_id = ?2",
+                rusqlite::params![id, item_id],
+            )?,
+        };
+        touch_review(conn, id)
+    }
+    
+    pub fn replace_review_decisions(id: i64, decisions: &[(&str, &str)]) -> Result<()> {
+        let conn = DB.lock().unwrap();
+        let conn = conn.as_ref().unwrap();
+        let tx = conn.unchecked_transaction()?;
+        
+        tx.execute("DELETE FROM review_decisions WHERE review_id = ?1", [id])?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO review_decisions (review_id, item_id, decision) VALUES (?1, ?2, ?3)"
+            )?;
+            for (item_id, decision) in decisions {
+                stmt.execute(rusqlite::params![id, item_id, decision])?;
+            }
+        }
+        touch_review(&tx, id)?;
+        
+        tx.commit()
+    }
+    
+    pub fn load_review_decisions(id: i64) -> Result<Vec<(String, String)>> {
+        let conn = DB.lock().unwrap();
+        let conn = conn.as_ref().unwrap();
+        
+        let mut stmt = conn.prepare(
+            "SELECT item_id, decision FROM review_decisions WHERE review_id = ?1"
+        )?;
+        
+        let decisions = stmt.query_map([id], |row| {
+            let item_id: String = row.get(0)?;
+            let decision: String = row.get(1)?;
+            Ok((item_id, decision))
+        })?;
+        
+        decisions.collect()
+    }
+    
+    fn touch_review(conn: &Connection, id: i64) -> Result<()> {
+        conn.execute(
+            "UPDATE review_sessions SET updated_at = CURRENT_TIMESTAMP WHERE id = ?1",
+            [id],
+        )?;
+        
+        Ok(())
+    }
+    
+    pub fn load_review(id: i64) -> Result<Option<Value>> {
+        let conn = DB.lock().unwrap();
+        let conn = conn.as_ref().unwrap();
+        
+        conn.query_row(
+            "SELECT session FROM review_sessions WHERE id = ?1",
+            [id],
+            |row| {
+                let json_str: String = row.get(0)?;
+                Ok(serde_json::from_str(&json_str).unwrap_or(Value::Null))
+            },
+        ).optional()
+    }
 }
 
 // 安全模块